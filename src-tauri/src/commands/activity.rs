@@ -0,0 +1,267 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::pricing;
+use super::transcript::{self, TokenUsage, UsageDedup};
+
+const RATE_WINDOW_MINUTES: i64 = 5;
+const SERIES_MINUTES: i64 = 30;
+
+static TRACKER: OnceLock<Mutex<ActivityTracker>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveActivity {
+    pub active_sessions: u32,
+    pub tokens_per_minute: f64,
+    // Priced usage only; models without rates are listed in `unpriced_models`.
+    pub cost_per_hour: f64,
+    pub unpriced_models: Vec<String>,
+    pub tool_calls_per_minute: f64,
+    pub window_minutes: i64,
+    pub tokens_series: Vec<u64>,
+    pub cost_series: Vec<f64>,
+    pub tool_calls_series: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct ActivityEvent {
+    at: DateTime<Utc>,
+    tokens: u64,
+    model: String,
+    // None when the model has no rates.
+    cost_usd: Option<f64>,
+    tool_calls: u32,
+}
+
+#[derive(Default)]
+pub struct ActivityTracker {
+    events: VecDeque<ActivityEvent>,
+    last_seen: HashMap<PathBuf, DateTime<Utc>>,
+    dedup: HashMap<PathBuf, UsageDedup>,
+}
+
+pub fn tracker() -> &'static Mutex<ActivityTracker> {
+    TRACKER.get_or_init(|| Mutex::new(ActivityTracker::default()))
+}
+
+impl ActivityTracker {
+    pub fn ingest(&mut self, path: &Path, entries: &[Value], now: DateTime<Utc>) {
+        let horizon = now - Duration::minutes(SERIES_MINUTES);
+
+        for entry in entries {
+            let at = match transcript::parse_timestamp(entry) {
+                Some(ts) => ts,
+                None => continue,
+            };
+            if at < horizon {
+                continue;
+            }
+
+            let seen = self.last_seen.entry(path.to_path_buf()).or_insert(at);
+            if at > *seen {
+                *seen = at;
+            }
+
+            if transcript::entry_type(entry) != "assistant" {
+                continue;
+            }
+
+            let message = entry.get("message").unwrap_or(&Value::Null);
+            let usage = TokenUsage::from_message(message).unwrap_or_default();
+            let usage = self
+                .dedup
+                .entry(path.to_path_buf())
+                .or_default()
+                .record(entry, usage)
+                .usage;
+            let model = message.get("model").and_then(|m| m.as_str()).unwrap_or("");
+            let tool_calls = transcript::content_blocks(entry)
+                .iter()
+                .filter(|b| transcript::block_type(b) == "tool_use")
+                .count() as u32;
+
+            self.events.push_back(ActivityEvent {
+                at,
+                tokens: usage.total(),
+                model: model.to_string(),
+                cost_usd: pricing::estimate_cost(model, &usage),
+                tool_calls,
+            });
+        }
+
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let horizon = now - Duration::minutes(SERIES_MINUTES);
+        self.events.retain(|e| e.at >= horizon);
+        self.last_seen.retain(|_, at| *at >= horizon);
+        let last_seen = &self.last_seen;
+        self.dedup.retain(|path, _| last_seen.contains_key(path));
+    }
+
+    pub fn snapshot(&mut self, now: DateTime<Utc>) -> LiveActivity {
+        self.prune(now);
+
+        let window_start = now - Duration::minutes(RATE_WINDOW_MINUTES);
+        let minutes = RATE_WINDOW_MINUTES as f64;
+
+        let (tokens, cost, tool_calls) = self.events.iter().filter(|e| e.at >= window_start).fold(
+            (0u64, 0.0f64, 0u32),
+            |(t, c, k), e| {
+                (
+                    t + e.tokens,
+                    c + e.cost_usd.unwrap_or(0.0),
+                    k + e.tool_calls,
+                )
+            },
+        );
+
+        let mut unpriced_models: Vec<String> = Vec::new();
+        for event in self.events.iter().filter(|e| e.at >= window_start) {
+            if event.cost_usd.is_none()
+                && event.tokens > 0
+                && !unpriced_models.contains(&event.model)
+            {
+                unpriced_models.push(event.model.clone());
+            }
+        }
+
+        let buckets = SERIES_MINUTES as usize;
+        let mut tokens_series = vec![0u64; buckets];
+        let mut cost_series = vec![0.0f64; buckets];
+        let mut tool_calls_series = vec![0u32; buckets];
+
+        for event in &self.events {
+            let age = (now - event.at).num_minutes().max(0) as usize;
+            if age >= buckets {
+                continue;
+            }
+            let idx = buckets - 1 - age;
+            tokens_series[idx] += event.tokens;
+            cost_series[idx] += event.cost_usd.unwrap_or(0.0);
+            tool_calls_series[idx] += event.tool_calls;
+        }
+
+        LiveActivity {
+            active_sessions: self
+                .last_seen
+                .values()
+                .filter(|at| **at >= window_start)
+                .count() as u32,
+            tokens_per_minute: tokens as f64 / minutes,
+            cost_per_hour: cost * 60.0 / minutes,
+            unpriced_models,
+            tool_calls_per_minute: tool_calls as f64 / minutes,
+            window_minutes: RATE_WINDOW_MINUTES,
+            tokens_series,
+            cost_series,
+            tool_calls_series,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_live_activity() -> Result<LiveActivity, String> {
+    let mut tracker = tracker().lock().map_err(|e| e.to_string())?;
+    Ok(tracker.snapshot(Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::at;
+    use serde_json::json;
+
+    fn assistant(ts: &str, output_tokens: u64, tools: usize) -> Value {
+        assistant_for("claude-sonnet-4-5", ts, output_tokens, tools)
+    }
+
+    fn assistant_for(model: &str, ts: &str, output_tokens: u64, tools: usize) -> Value {
+        let content: Vec<Value> = (0..tools)
+            .map(|i| json!({"type": "tool_use", "id": format!("tu_{}", i), "name": "Bash"}))
+            .collect();
+        json!({
+            "type": "assistant",
+            "timestamp": ts,
+            "message": {
+                "model": model,
+                "content": content,
+                "usage": {"input_tokens": 0, "output_tokens": output_tokens}
+            }
+        })
+    }
+
+    #[test]
+    fn test_snapshot_rates_over_window() {
+        let mut tracker = ActivityTracker::default();
+        let now = at("2025-06-01T12:00:00Z");
+
+        tracker.ingest(
+            Path::new("/p/a.jsonl"),
+            &[
+                assistant("2025-06-01T11:58:00Z", 1_000_000, 2),
+                assistant("2025-06-01T11:59:30Z", 500_000, 3),
+            ],
+            now,
+        );
+        tracker.ingest(
+            Path::new("/p/b.jsonl"),
+            &[assistant("2025-06-01T11:40:00Z", 2_000_000, 1)],
+            now,
+        );
+
+        let live = tracker.snapshot(now);
+        assert_eq!(live.active_sessions, 1);
+        assert_eq!(live.tokens_per_minute, 300_000.0);
+        assert_eq!(live.tool_calls_per_minute, 1.0);
+        assert!((live.cost_per_hour - 22.5 * 12.0).abs() < 1e-9);
+
+        assert_eq!(live.tokens_series.len(), SERIES_MINUTES as usize);
+        assert_eq!(live.tokens_series[29], 500_000);
+        assert_eq!(live.tokens_series[27], 1_000_000);
+        assert_eq!(live.tokens_series[9], 2_000_000);
+        assert_eq!(live.tool_calls_series.iter().sum::<u32>(), 6);
+        assert!(live.unpriced_models.is_empty());
+    }
+
+    #[test]
+    fn test_unpriced_models_are_flagged() {
+        let mut tracker = ActivityTracker::default();
+        let now = at("2025-06-01T12:00:00Z");
+
+        tracker.ingest(
+            Path::new("/p/a.jsonl"),
+            &[
+                assistant("2025-06-01T11:59:00Z", 1_000_000, 0),
+                assistant_for("mystery-model", "2025-06-01T11:59:30Z", 1_000_000, 0),
+                assistant_for("<synthetic>", "2025-06-01T11:59:40Z", 0, 0),
+            ],
+            now,
+        );
+
+        let live = tracker.snapshot(now);
+        assert_eq!(live.unpriced_models, vec!["mystery-model"]);
+        assert!((live.cost_per_hour - 15.0 * 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_old_entries_are_ignored() {
+        let mut tracker = ActivityTracker::default();
+        let now = at("2025-06-01T12:00:00Z");
+
+        tracker.ingest(
+            Path::new("/p/a.jsonl"),
+            &[assistant("2025-06-01T09:00:00Z", 1000, 1)],
+            now,
+        );
+
+        let live = tracker.snapshot(now);
+        assert_eq!(live.active_sessions, 0);
+        assert_eq!(live.tokens_series.iter().sum::<u64>(), 0);
+    }
+}
@@ -0,0 +1,688 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::pricing::{self, Pricing};
+use super::transcript::TokenUsage;
+use super::usage::{
+    self, DayRange, FileEditEvent, SessionChanges, ToolCallEvent, UsageEngine, UsageEvent,
+    UsageReport, UsageRow,
+};
+use super::utils;
+
+static DB: OnceLock<Mutex<AnalyticsDb>> = OnceLock::new();
+
+// Applied in order, each in its own transaction; `PRAGMA user_version`
+// records how many have run.
+const MIGRATIONS: &[&str] = &[r#"
+CREATE TABLE sessions (
+    project TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    first_at TEXT,
+    last_at TEXT,
+    PRIMARY KEY (project, session_id)
+);
+
+CREATE TABLE messages (
+    project TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    at TEXT NOT NULL,
+    day TEXT NOT NULL,
+    model TEXT NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cache_creation_tokens INTEGER NOT NULL,
+    cache_creation_1h_tokens INTEGER NOT NULL,
+    cache_read_tokens INTEGER NOT NULL,
+    PRIMARY KEY (project, session_id, seq)
+);
+CREATE INDEX messages_day ON messages (day);
+
+CREATE TABLE tool_calls (
+    project TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    at TEXT NOT NULL,
+    day TEXT NOT NULL,
+    name TEXT NOT NULL,
+    subject TEXT,
+    is_error INTEGER,
+    latency_ms INTEGER,
+    PRIMARY KEY (project, session_id, seq)
+);
+CREATE INDEX tool_calls_day ON tool_calls (day);
+
+-- `seq` is the row of the tool call that made the edit.
+CREATE TABLE file_edits (
+    project TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    file_path TEXT NOT NULL,
+    lines_added INTEGER NOT NULL,
+    lines_removed INTEGER NOT NULL,
+    PRIMARY KEY (project, session_id, seq)
+);
+"#];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Day,
+    Model,
+    Project,
+    Session,
+}
+
+pub struct AnalyticsDb {
+    conn: Connection,
+}
+
+fn db_path() -> Result<PathBuf, String> {
+    Ok(utils::cockpit_dir()?.join("analytics.db"))
+}
+
+fn db() -> Result<&'static Mutex<AnalyticsDb>, String> {
+    if let Some(db) = DB.get() {
+        return Ok(db);
+    }
+    let db = AnalyticsDb::open(&db_path()?)?;
+    Ok(DB.get_or_init(|| Mutex::new(db)))
+}
+
+fn parse_at(value: String) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+impl AnalyticsDb {
+    pub fn open(path: &Path) -> Result<AnalyticsDb, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<AnalyticsDb, String> {
+        Self::init(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn init(mut conn: Connection) -> Result<AnalyticsDb, String> {
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+            // A crash mid-step rolls back, so the step reruns next launch.
+            conn.transaction()
+                .and_then(|tx| {
+                    tx.execute_batch(sql)?;
+                    tx.pragma_update(None, "user_version", i + 1)?;
+                    tx.commit()
+                })
+                .map_err(|e| format!("Failed to migrate analytics database: {}", e))?;
+        }
+        Ok(AnalyticsDb { conn })
+    }
+
+    pub fn apply(&mut self, changes: &[SessionChanges]) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        {
+            let mut session = tx
+                .prepare_cached(
+                    "INSERT INTO sessions (project, session_id, file_path, first_at, last_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (project, session_id) DO UPDATE SET
+                        file_path = excluded.file_path,
+                        first_at = excluded.first_at,
+                        last_at = excluded.last_at",
+                )
+                .map_err(|e| e.to_string())?;
+            let mut message = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO messages
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                )
+                .map_err(|e| e.to_string())?;
+            let mut tool_call = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO tool_calls VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )
+                .map_err(|e| e.to_string())?;
+            let mut edit = tx
+                .prepare_cached("INSERT OR REPLACE INTO file_edits VALUES (?1, ?2, ?3, ?4, ?5, ?6)")
+                .map_err(|e| e.to_string())?;
+
+            for change in changes {
+                session
+                    .execute(params![
+                        change.project,
+                        change.session_id,
+                        change.file_path.to_string_lossy(),
+                        change.first_timestamp.map(|at| at.to_rfc3339()),
+                        change.last_timestamp.map(|at| at.to_rfc3339()),
+                    ])
+                    .map_err(|e| e.to_string())?;
+
+                // A transcript that was rewritten may now hold fewer rows.
+                tx.execute(
+                    "DELETE FROM messages WHERE project = ?1 AND session_id = ?2 AND seq >= ?3",
+                    params![change.project, change.session_id, change.message_count],
+                )
+                .and_then(|_| {
+                    tx.execute(
+                        "DELETE FROM tool_calls
+                         WHERE project = ?1 AND session_id = ?2 AND seq >= ?3",
+                        params![change.project, change.session_id, change.tool_call_count],
+                    )
+                })
+                .and_then(|_| {
+                    tx.execute(
+                        "DELETE FROM file_edits
+                         WHERE project = ?1 AND session_id = ?2 AND seq >= ?3",
+                        params![change.project, change.session_id, change.tool_calls_from],
+                    )
+                })
+                .map_err(|e| e.to_string())?;
+
+                for (i, event) in change.messages.iter().enumerate() {
+                    let tokens = &event.tokens;
+                    message
+                        .execute(params![
+                            change.project,
+                            change.session_id,
+                            change.messages_from + i,
+                            event.at.to_rfc3339(),
+                            usage::local_day(event.at),
+                            event.model,
+                            tokens.input_tokens,
+                            tokens.output_tokens,
+                            tokens.cache_creation_input_tokens,
+                            tokens.cache_creation_1h_input_tokens,
+                            tokens.cache_read_input_tokens,
+                        ])
+                        .map_err(|e| e.to_string())?;
+                }
+                for (i, call) in change.tool_calls.iter().enumerate() {
+                    tool_call
+                        .execute(params![
+                            change.project,
+                            change.session_id,
+                            change.tool_calls_from + i,
+                            call.at.to_rfc3339(),
+                            usage::local_day(call.at),
+                            call.name,
+                            call.subject,
+                            call.is_error,
+                            call.latency_ms,
+                        ])
+                        .map_err(|e| e.to_string())?;
+                }
+                for (seq, event) in &change.edits {
+                    edit.execute(params![
+                        change.project,
+                        change.session_id,
+                        seq,
+                        event.file_path,
+                        event.lines_added,
+                        event.lines_removed,
+                    ])
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn events(
+        &self,
+        range: DayRange,
+        project: Option<&str>,
+    ) -> Result<Vec<UsageEvent>, String> {
+        let (from, to) = range.bounds();
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT at, session_id, project, model, input_tokens, output_tokens,
+                        cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens
+                 FROM messages
+                 WHERE (?1 IS NULL OR day >= ?1) AND (?2 IS NULL OR day <= ?2)
+                   AND (?3 IS NULL OR project = ?3)",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![from, to, project], |row| {
+                Ok(UsageEvent {
+                    at: parse_at(row.get(0)?)?,
+                    session_id: row.get(1)?,
+                    project: row.get(2)?,
+                    model: row.get(3)?,
+                    tokens: TokenUsage {
+                        input_tokens: row.get(4)?,
+                        output_tokens: row.get(5)?,
+                        cache_creation_input_tokens: row.get(6)?,
+                        cache_creation_1h_input_tokens: row.get(7)?,
+                        cache_read_input_tokens: row.get(8)?,
+                    },
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    pub fn tool_calls(
+        &self,
+        range: DayRange,
+        project: Option<&str>,
+    ) -> Result<Vec<ToolCallEvent>, String> {
+        let (from, to) = range.bounds();
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT at, session_id, project, name, subject, is_error, latency_ms
+                 FROM tool_calls
+                 WHERE (?1 IS NULL OR day >= ?1) AND (?2 IS NULL OR day <= ?2)
+                   AND (?3 IS NULL OR project = ?3)",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![from, to, project], |row| {
+                Ok(ToolCallEvent {
+                    at: parse_at(row.get(0)?)?,
+                    session_id: row.get(1)?,
+                    project: row.get(2)?,
+                    name: row.get(3)?,
+                    subject: row.get(4)?,
+                    is_error: row.get(5)?,
+                    latency_ms: row.get(6)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    // Edits whose call failed are left out; the edit never happened.
+    pub fn file_edits(
+        &self,
+        range: DayRange,
+        project: Option<&str>,
+    ) -> Result<Vec<FileEditEvent>, String> {
+        let (from, to) = range.bounds();
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT t.at, e.session_id, e.project, e.file_path, e.lines_added, e.lines_removed
+                 FROM file_edits e JOIN tool_calls t USING (project, session_id, seq)
+                 WHERE (t.is_error IS NULL OR t.is_error = 0)
+                   AND (?1 IS NULL OR t.day >= ?1) AND (?2 IS NULL OR t.day <= ?2)
+                   AND (?3 IS NULL OR e.project = ?3)",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![from, to, project], |row| {
+                Ok(FileEditEvent {
+                    at: parse_at(row.get(0)?)?,
+                    session_id: row.get(1)?,
+                    project: row.get(2)?,
+                    file_path: row.get(3)?,
+                    lines_added: row.get(4)?,
+                    lines_removed: row.get(5)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    pub fn session_files(&self) -> Result<HashMap<(String, String), String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT project, session_id, file_path FROM sessions")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    pub fn usage_rows(
+        &self,
+        group_by: GroupBy,
+        range: DayRange,
+        project: Option<&str>,
+    ) -> Result<Vec<UsageRow>, String> {
+        let key = match group_by {
+            GroupBy::Day => "day",
+            GroupBy::Model => "model",
+            GroupBy::Project => "project",
+            GroupBy::Session => "session_id",
+        };
+        let (from, to) = range.bounds();
+        let mut stmt = self
+            .conn
+            .prepare_cached(&format!(
+                "SELECT {key}, model, COUNT(*), SUM(input_tokens), SUM(output_tokens),
+                        SUM(cache_creation_tokens), SUM(cache_creation_1h_tokens),
+                        SUM(cache_read_tokens)
+                 FROM messages
+                 WHERE (?1 IS NULL OR day >= ?1) AND (?2 IS NULL OR day <= ?2)
+                   AND (?3 IS NULL OR project = ?3)
+                 GROUP BY 1, 2"
+            ))
+            .map_err(|e| e.to_string())?;
+        let groups = stmt
+            .query_map(params![from, to, project], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u64>(2)?,
+                    TokenUsage {
+                        input_tokens: row.get(3)?,
+                        output_tokens: row.get(4)?,
+                        cache_creation_input_tokens: row.get(5)?,
+                        cache_creation_1h_input_tokens: row.get(6)?,
+                        cache_read_input_tokens: row.get(7)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        let pricing = pricing::current();
+        let mut rows: BTreeMap<String, UsageRow> = BTreeMap::new();
+        for group in groups {
+            let (key, model, messages, tokens) = group.map_err(|e| e.to_string())?;
+            let row = rows.entry(key.clone()).or_insert_with(|| UsageRow {
+                key,
+                ..UsageRow::default()
+            });
+            row.messages += messages;
+            row.tokens.add(&tokens);
+            row.total_tokens = row.tokens.total();
+            row.cost.add(&model, &tokens, &pricing);
+        }
+
+        let mut rows: Vec<UsageRow> = rows.into_values().collect();
+        if group_by != GroupBy::Day {
+            rows.sort_by_key(|r| std::cmp::Reverse(r.total_tokens));
+        }
+        Ok(rows)
+    }
+}
+
+// Writes what the engine has read since the last sync. The engine lock is held
+// throughout so nothing is marked synced unless it was written.
+pub fn flush(engine: &mut UsageEngine) -> Result<(), String> {
+    let changes = engine.pending_changes();
+    if changes.is_empty() {
+        return Ok(());
+    }
+    db()?.lock().map_err(|e| e.to_string())?.apply(&changes)?;
+    engine.mark_synced(&changes);
+    Ok(())
+}
+
+// Brings the database up to date with every transcript on disk.
+pub fn sync() -> Result<(), String> {
+    let mut engine = usage::engine().lock().map_err(|e| e.to_string())?;
+    engine.refresh(&utils::projects_dir());
+    flush(&mut engine).map_err(|e| format!("Failed to record usage history: {}", e))
+}
+
+// Called before transcripts are deleted so their usage outlives them. A
+// database that can't be written only loses that history; the deletion still
+// goes ahead.
+pub fn sync_before_delete() {
+    if let Err(e) = sync() {
+        eprintln!("{}", e);
+    }
+}
+
+pub fn load_events(range: DayRange, project: Option<&str>) -> Result<Vec<UsageEvent>, String> {
+    sync()?;
+    db()?
+        .lock()
+        .map_err(|e| e.to_string())?
+        .events(range, project)
+}
+
+pub fn load_tool_calls(
+    range: DayRange,
+    project: Option<&str>,
+) -> Result<Vec<ToolCallEvent>, String> {
+    sync()?;
+    db()?
+        .lock()
+        .map_err(|e| e.to_string())?
+        .tool_calls(range, project)
+}
+
+pub fn load_file_edits(
+    range: DayRange,
+    project: Option<&str>,
+) -> Result<Vec<FileEditEvent>, String> {
+    sync()?;
+    db()?
+        .lock()
+        .map_err(|e| e.to_string())?
+        .file_edits(range, project)
+}
+
+pub fn load_report(pricing: &Pricing) -> Result<UsageReport, String> {
+    sync()?;
+    let db = db()?.lock().map_err(|e| e.to_string())?;
+    let events = db.events(DayRange::default(), None)?;
+    Ok(usage::build_report(&events, &db.session_files()?, pricing))
+}
+
+#[tauri::command(async)]
+pub fn get_usage_history(
+    group_by: GroupBy,
+    from: Option<String>,
+    to: Option<String>,
+    project: Option<String>,
+) -> Result<Vec<UsageRow>, String> {
+    let range = DayRange::parse(&from, &to)?;
+    sync()?;
+    db()?
+        .lock()
+        .map_err(|e| e.to_string())?
+        .usage_rows(group_by, range, project.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::{at, event};
+    use serde_json::json;
+    use std::io::Write;
+
+    fn append(path: &Path, entries: &[serde_json::Value]) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        for entry in entries {
+            writeln!(file, "{}", entry).unwrap();
+        }
+    }
+
+    fn assistant(ts: &str, id: &str, output: u64, tool: Option<&str>) -> serde_json::Value {
+        let content: Vec<serde_json::Value> = tool
+            .map(|t| {
+                json!({"type": "tool_use", "id": t, "name": "Bash", "input": {"command": "cargo test"}})
+            })
+            .into_iter()
+            .collect();
+        json!({
+            "type": "assistant",
+            "cwd": "/work/app",
+            "timestamp": ts,
+            "requestId": format!("req_{}", id),
+            "message": {
+                "id": id,
+                "model": "claude-sonnet-4-5",
+                "content": content,
+                "usage": {"input_tokens": 10, "output_tokens": output}
+            }
+        })
+    }
+
+    #[test]
+    fn test_usage_survives_transcript_deletion() {
+        let dir = std::env::temp_dir().join(format!("cockpit-analytics-db-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("-work-app")).unwrap();
+        let path = dir.join("-work-app").join("s1.jsonl");
+
+        let mut engine = UsageEngine::default();
+        let mut db = AnalyticsDb::open_in_memory().unwrap();
+        let mut sync = |engine: &mut UsageEngine| {
+            engine.refresh(&dir);
+            let changes = engine.pending_changes();
+            db.apply(&changes).unwrap();
+            engine.mark_synced(&changes);
+            changes.len()
+        };
+
+        append(
+            &path,
+            &[
+                assistant("2025-06-01T12:00:00Z", "msg_1", 1, Some("tu_1")),
+                json!({
+                    "type": "user",
+                    "timestamp": "2025-06-01T12:00:03Z",
+                    "message": {"content": [{"type": "tool_result", "tool_use_id": "tu_1"}]}
+                }),
+            ],
+        );
+        assert_eq!(sync(&mut engine), 1);
+        assert_eq!(sync(&mut engine), 0);
+
+        // A streamed continuation updates the stored row instead of adding one.
+        append(
+            &path,
+            &[
+                assistant("2025-06-01T12:00:00Z", "msg_1", 40, None),
+                assistant("2025-06-02T09:00:00Z", "msg_2", 5, None),
+            ],
+        );
+        assert_eq!(sync(&mut engine), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+        engine.refresh(&dir);
+        assert!(engine.pending_changes().is_empty());
+
+        let events = db.events(DayRange::default(), Some("/work/app")).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events.iter().map(|e| e.tokens.output_tokens).sum::<u64>(),
+            45
+        );
+
+        let calls = db.tool_calls(DayRange::default(), None).unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].latency_ms, Some(3000));
+        assert_eq!(calls[0].subject.as_deref(), Some("cargo test"));
+    }
+
+    #[test]
+    fn test_sessions_and_edits_keyed_by_project() {
+        let mut db = AnalyticsDb::open_in_memory().unwrap();
+        let at = at("2025-06-02T12:00:00Z");
+        let changes = |project: &str, is_error: bool| {
+            let edit = FileEditEvent {
+                at,
+                session_id: "s1".to_string(),
+                project: project.to_string(),
+                file_path: format!("{}/src/main.rs", project),
+                lines_added: 2,
+                lines_removed: 1,
+            };
+            SessionChanges {
+                file_path: PathBuf::from(format!("/p/{}/s1.jsonl", project.replace('/', "-"))),
+                session_id: "s1".to_string(),
+                project: project.to_string(),
+                first_timestamp: Some(at),
+                last_timestamp: Some(at),
+                message_count: 0,
+                messages_from: 0,
+                messages: Vec::new(),
+                tool_call_count: 1,
+                tool_calls_from: 0,
+                tool_calls: vec![ToolCallEvent {
+                    at,
+                    session_id: "s1".to_string(),
+                    project: project.to_string(),
+                    name: "Edit".to_string(),
+                    subject: Some(edit.file_path.clone()),
+                    is_error: Some(is_error),
+                    latency_ms: Some(5),
+                }],
+                edits: vec![(0, edit)],
+            }
+        };
+        db.apply(&[changes("/work/app", false), changes("/work/lib", true)])
+            .unwrap();
+
+        assert_eq!(db.session_files().unwrap().len(), 2);
+        assert_eq!(db.tool_calls(DayRange::default(), None).unwrap().len(), 2);
+
+        let edits = db.file_edits(DayRange::default(), None).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].file_path, "/work/app/src/main.rs");
+        assert_eq!((edits[0].lines_added, edits[0].lines_removed), (2, 1));
+    }
+
+    #[test]
+    fn test_usage_rows_group_and_filter() {
+        let mut db = AnalyticsDb::open_in_memory().unwrap();
+        db.apply(&[SessionChanges {
+            file_path: PathBuf::from("/p/-work-app/s1.jsonl"),
+            session_id: "s1".to_string(),
+            project: "/work/app".to_string(),
+            first_timestamp: Some(at("2025-06-01T12:00:00Z")),
+            last_timestamp: Some(at("2025-06-03T12:00:00Z")),
+            message_count: 3,
+            messages_from: 0,
+            messages: vec![
+                event("2025-06-01T12:00:00Z").output(1_000_000),
+                event("2025-06-03T12:00:00Z").output(10),
+                event("2025-06-03T12:00:00Z")
+                    .model("mystery-model")
+                    .output(20),
+            ],
+            tool_call_count: 0,
+            tool_calls_from: 0,
+            tool_calls: Vec::new(),
+            edits: Vec::new(),
+        }])
+        .unwrap();
+
+        let rows = db
+            .usage_rows(GroupBy::Model, DayRange::default(), None)
+            .unwrap();
+        assert_eq!(rows[0].key, "claude-sonnet-4-5");
+        assert_eq!((rows[0].messages, rows[0].total_tokens), (2, 1_000_010));
+        assert_eq!(rows[1].cost.unpriced_models, vec!["mystery-model"]);
+
+        let range = DayRange::parse(&Some("2025-06-02".to_string()), &None).unwrap();
+        let rows = db.usage_rows(GroupBy::Project, range, None).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].messages, 2);
+        assert!(db
+            .usage_rows(GroupBy::Day, range, Some("/work/lib"))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::utils;
+
+// Previous versions kept per file; older ones are pruned on each write.
+const BACKUP_LIMIT: usize = 10;
+const SOURCE_FILE: &str = "source";
+const ID_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub file: String,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+fn backups_root() -> Result<PathBuf, String> {
+    Ok(utils::cockpit_dir()?.join("backups"))
+}
+
+// One directory per file. Names are lossy, so each records the path it holds
+// and a clash moves on to a numbered sibling.
+fn backup_dir(root: &Path, path: &Path) -> PathBuf {
+    let source = path.to_string_lossy();
+    let base = utils::encode_project_path(&source);
+    (0..)
+        .map(|n| match n {
+            0 => root.join(&base),
+            n => root.join(format!("{}-{}", base, n)),
+        })
+        .find(|dir| match fs::read_to_string(dir.join(SOURCE_FILE)) {
+            Ok(existing) => existing == source,
+            Err(_) => true,
+        })
+        .expect("unbounded range always yields a directory")
+}
+
+fn backup_ids(dir: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .filter(|name| name.ends_with(".bak"))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Ids are timestamps, so newest sorts last.
+    ids.sort();
+    ids
+}
+
+fn backup(root: &Path, path: &Path, next: &[u8]) -> Result<(), String> {
+    let current = match fs::read(path) {
+        Ok(current) if current == next => return Ok(()),
+        Ok(current) => current,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let dir = backup_dir(root, path);
+    let mut ids = backup_ids(&dir);
+    if let Some(latest) = ids.last() {
+        if fs::read(dir.join(latest)).ok().as_deref() == Some(current.as_slice()) {
+            return Ok(());
+        }
+    }
+
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create directory {}: {}", dir.display(), e))?;
+    fs::write(dir.join(SOURCE_FILE), path.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    let mut at = Utc::now();
+    let mut id = format!("{}.bak", at.format(ID_FORMAT));
+    while dir.join(&id).exists() {
+        at += chrono::Duration::microseconds(1);
+        id = format!("{}.bak", at.format(ID_FORMAT));
+    }
+    write_atomic(&dir.join(&id), &current)?;
+    ids.push(id);
+
+    let excess = ids.len().saturating_sub(BACKUP_LIMIT);
+    for old in &ids[..excess] {
+        let _ = fs::remove_file(dir.join(old));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+// Readers either see the old file or the new one, never a partial write. A
+// symlinked file, e.g. one kept in a dotfiles repo, has its target replaced so
+// the link survives.
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let path = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => fs::canonicalize(path)
+            .map_err(|e| format!("Failed to resolve link {}: {}", path.display(), e))?,
+        _ => path.to_path_buf(),
+    };
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp = parent.join(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| -> io::Result<()> {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        if let Ok(meta) = fs::metadata(&path) {
+            file.set_permissions(meta.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        sync_dir(parent);
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn write_file_in(root: &Path, path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+    }
+    backup(root, path, contents)?;
+    write_atomic(path, contents)
+}
+
+// Every config file cockpit edits goes through here: the previous version is
+// backed up, then the new one is swapped in atomically.
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write_file_in(&backups_root()?, path, contents)
+}
+
+fn list_in(root: &Path, file: Option<&str>) -> Vec<BackupInfo> {
+    let dirs: Vec<PathBuf> = match file {
+        Some(file) => vec![backup_dir(root, Path::new(file))],
+        None => fs::read_dir(root)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default(),
+    };
+
+    let mut backups: Vec<BackupInfo> = dirs
+        .iter()
+        .filter_map(|dir| {
+            let source = fs::read_to_string(dir.join(SOURCE_FILE)).ok()?;
+            Some((dir, source))
+        })
+        .flat_map(|(dir, source)| {
+            backup_ids(dir).into_iter().filter_map(move |id| {
+                let stamp = id.strip_suffix(".bak")?;
+                let created_at = NaiveDateTime::parse_from_str(stamp, ID_FORMAT)
+                    .ok()?
+                    .and_utc();
+                let size = fs::metadata(dir.join(&id)).ok()?.len();
+                Some(BackupInfo {
+                    id,
+                    file: source.clone(),
+                    created_at,
+                    size,
+                })
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(a.file.cmp(&b.file)));
+    backups
+}
+
+fn restore_in(root: &Path, file: &str, id: &str) -> Result<(), String> {
+    utils::validate_safe_name(id)?;
+    if !id.ends_with(".bak") {
+        return Err(format!("Backup {} not found", id));
+    }
+    let path = Path::new(file);
+    let dir = backup_dir(root, path);
+    if !dir.join(SOURCE_FILE).exists() {
+        return Err(format!("No backups for {}", file));
+    }
+    let contents = fs::read(dir.join(id)).map_err(|_| format!("Backup {} not found", id))?;
+    write_file_in(root, path, &contents)
+}
+
+#[tauri::command]
+pub fn list_backups(file: Option<String>) -> Result<Vec<BackupInfo>, String> {
+    Ok(list_in(&backups_root()?, file.as_deref()))
+}
+
+// The version being replaced is backed up too, so a restore can be undone.
+#[tauri::command]
+pub fn restore_backup(file: String, id: String) -> Result<(), String> {
+    restore_in(&backups_root()?, &file, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> (PathBuf, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("cockpit-backups-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        (
+            dir.join("backups"),
+            dir.join("project/.claude/settings.json"),
+        )
+    }
+
+    #[test]
+    fn test_writes_rotate_backups() {
+        let (root, path) = fixture("rotate");
+        for n in 0..BACKUP_LIMIT + 3 {
+            write_file_in(&root, &path, format!("v{}", n).as_bytes()).unwrap();
+        }
+        // Rewriting identical content doesn't push out an older version.
+        write_file_in(&root, &path, b"v12").unwrap();
+        write_file_in(&root, &path, b"v12").unwrap();
+
+        let backups = list_in(&root, Some(&path.to_string_lossy()));
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name())
+            .collect();
+        let contents: Vec<String> = backups
+            .iter()
+            .map(|b| fs::read_to_string(backup_dir(&root, &path).join(&b.id)).unwrap())
+            .collect();
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+
+        assert_eq!(backups.len(), BACKUP_LIMIT);
+        assert_eq!(contents.first().map(String::as_str), Some("v11"));
+        assert_eq!(contents.last().map(String::as_str), Some("v2"));
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("settings.json")]);
+    }
+
+    #[test]
+    fn test_restore_backs_up_current_version() {
+        let (root, path) = fixture("restore");
+        write_file_in(&root, &path, b"first").unwrap();
+        write_file_in(&root, &path, b"second").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        let first = list_in(&root, Some(&file)).remove(0);
+        restore_in(&root, &file, &first.id).unwrap();
+        let restored = fs::read_to_string(&path).unwrap();
+        let backups = list_in(&root, None);
+        let missing = restore_in(&root, "/nowhere/settings.json", &first.id);
+        let escape = restore_in(&root, &file, "../source");
+        let not_backup = restore_in(&root, &file, SOURCE_FILE);
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+
+        assert_eq!(restored, "first");
+        assert_eq!(backups.len(), 2);
+        assert!(backups.iter().all(|b| b.file == file));
+        assert!(missing.is_err());
+        assert!(escape.is_err());
+        assert!(not_backup.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_writes_follow_symlinks() {
+        let (root, path) = fixture("symlink");
+        let target = root.parent().unwrap().join("dotfiles/settings.json");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&target, "old").unwrap();
+        std::os::unix::fs::symlink(&target, &path).unwrap();
+
+        write_file_in(&root, &path, b"new").unwrap();
+        let is_link = fs::symlink_metadata(&path)
+            .unwrap()
+            .file_type()
+            .is_symlink();
+        let contents = fs::read_to_string(&target).unwrap();
+        let backups = list_in(&root, Some(&path.to_string_lossy()));
+        let leftovers = fs::read_dir(target.parent().unwrap()).unwrap().count();
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+
+        assert!(is_link);
+        assert_eq!(contents, "new");
+        assert_eq!(backups.len(), 1);
+        assert_eq!(leftovers, 1);
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::Serialize;
+
+use super::analytics_db;
+use super::config;
+use super::pricing::{self, CostEstimate, Pricing};
+use super::transcript::TokenUsage;
+use super::usage::{DayRange, UsageEvent};
+
+const BLOCK_HOURS: i64 = 5;
+const RECENT_BLOCKS: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageBlock {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub first_activity: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub messages: u64,
+    pub tokens: TokenUsage,
+    pub total_tokens: u64,
+    pub cost: CostEstimate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitSource {
+    Config,
+    HistoricalMax,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockStatus {
+    pub current: Option<UsageBlock>,
+    pub resets_in_secs: Option<i64>,
+    pub tokens_per_minute: f64,
+    pub cost_per_hour: f64,
+    pub projected_tokens: u64,
+    pub projected_cost_usd: f64,
+    pub token_limit: Option<u64>,
+    pub limit_source: Option<LimitSource>,
+    pub will_hit_limit: bool,
+    pub minutes_until_limit: Option<f64>,
+    pub recent: Vec<UsageBlock>,
+}
+
+// A block opens at the hour of the first message after a quiet spell and
+// covers the following five hours; the next message after it closes (or after
+// five idle hours) opens a new one. This mirrors how plan usage resets.
+pub fn split_blocks(events: &mut [UsageEvent], pricing: &Pricing) -> Vec<UsageBlock> {
+    events.sort_by_key(|e| e.at);
+
+    let length = Duration::hours(BLOCK_HOURS);
+    let mut blocks: Vec<UsageBlock> = Vec::new();
+
+    for event in events.iter() {
+        let open = blocks
+            .last()
+            .is_some_and(|b| event.at < b.end && event.at - b.last_activity < length);
+
+        if !open {
+            let start = event
+                .at
+                .duration_trunc(Duration::hours(1))
+                .unwrap_or(event.at);
+            blocks.push(UsageBlock {
+                start,
+                end: start + length,
+                first_activity: event.at,
+                last_activity: event.at,
+                messages: 0,
+                tokens: TokenUsage::default(),
+                total_tokens: 0,
+                cost: CostEstimate::default(),
+            });
+        }
+
+        if let Some(block) = blocks.last_mut() {
+            block.last_activity = event.at;
+            block.messages += 1;
+            block.tokens.add(&event.tokens);
+            block.total_tokens = block.tokens.total();
+            block.cost.add(&event.model, &event.tokens, pricing);
+        }
+    }
+
+    blocks
+}
+
+pub fn block_status(
+    mut blocks: Vec<UsageBlock>,
+    configured_limit: Option<u64>,
+    now: DateTime<Utc>,
+) -> BlockStatus {
+    let current = blocks
+        .last()
+        .filter(|b| now < b.end && b.start <= now)
+        .cloned();
+
+    let history_end = if current.is_some() {
+        blocks.len() - 1
+    } else {
+        blocks.len()
+    };
+    let historical_max = blocks[..history_end].iter().map(|b| b.total_tokens).max();
+
+    let (token_limit, limit_source) = match (configured_limit, historical_max) {
+        (Some(limit), _) => (Some(limit), Some(LimitSource::Config)),
+        (None, Some(max)) if max > 0 => (Some(max), Some(LimitSource::HistoricalMax)),
+        _ => (None, None),
+    };
+
+    let mut status = BlockStatus {
+        current: None,
+        resets_in_secs: None,
+        tokens_per_minute: 0.0,
+        cost_per_hour: 0.0,
+        projected_tokens: 0,
+        projected_cost_usd: 0.0,
+        token_limit,
+        limit_source,
+        will_hit_limit: false,
+        minutes_until_limit: None,
+        recent: Vec::new(),
+    };
+
+    if let Some(block) = &current {
+        let elapsed = (now - block.first_activity).num_seconds().max(60) as f64 / 60.0;
+        let remaining = (block.end - now).num_seconds().max(0) as f64 / 60.0;
+
+        status.tokens_per_minute = block.total_tokens as f64 / elapsed;
+        status.cost_per_hour = block.cost.cost_usd * 60.0 / elapsed;
+        status.projected_tokens =
+            block.total_tokens + (status.tokens_per_minute * remaining).round() as u64;
+        status.projected_cost_usd = block.cost.cost_usd + status.cost_per_hour * remaining / 60.0;
+        status.resets_in_secs = Some((block.end - now).num_seconds().max(0));
+
+        if let Some(limit) = token_limit {
+            status.will_hit_limit = status.projected_tokens >= limit;
+            if block.total_tokens >= limit {
+                status.minutes_until_limit = Some(0.0);
+            } else if status.tokens_per_minute > 0.0 {
+                let minutes = (limit - block.total_tokens) as f64 / status.tokens_per_minute;
+                if minutes <= remaining {
+                    status.minutes_until_limit = Some(minutes);
+                }
+            }
+        }
+    }
+
+    blocks.reverse();
+    blocks.truncate(RECENT_BLOCKS);
+    status.recent = blocks;
+    status.current = current;
+    status
+}
+
+#[tauri::command(async)]
+pub fn get_usage_blocks() -> Result<BlockStatus, String> {
+    let mut events = analytics_db::load_events(DayRange::default(), None)?;
+    let limit = config::load_config()?.block_token_limit;
+
+    let blocks = split_blocks(&mut events, &pricing::current());
+    Ok(block_status(blocks, limit, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::{at, event};
+
+    #[test]
+    fn test_split_blocks_on_five_hour_windows() {
+        let mut events = vec![
+            event("2025-06-01T14:10:00Z").output(10),
+            event("2025-06-01T09:20:00Z").output(100),
+            event("2025-06-01T13:59:00Z").output(1),
+            event("2025-06-01T09:45:00Z").output(200),
+            event("2025-06-01T22:00:00Z").output(5),
+        ];
+        let blocks = split_blocks(&mut events, &Pricing::default());
+
+        let spans: Vec<(DateTime<Utc>, u64)> =
+            blocks.iter().map(|b| (b.start, b.total_tokens)).collect();
+        assert_eq!(
+            spans,
+            vec![
+                (at("2025-06-01T09:00:00Z"), 301),
+                (at("2025-06-01T14:00:00Z"), 10),
+                (at("2025-06-01T22:00:00Z"), 5),
+            ]
+        );
+        assert_eq!(blocks[0].end, at("2025-06-01T14:00:00Z"));
+        assert_eq!(blocks[0].last_activity, at("2025-06-01T13:59:00Z"));
+    }
+
+    #[test]
+    fn test_block_status_projects_limit() {
+        let mut events = vec![
+            event("2025-06-01T01:00:00Z").output(1000),
+            event("2025-06-01T10:05:00Z").output(300),
+            event("2025-06-01T11:00:00Z").output(300),
+        ];
+        let blocks = split_blocks(&mut events, &Pricing::default());
+        let status = block_status(blocks, None, at("2025-06-01T11:05:00Z"));
+
+        let current = status.current.as_ref().unwrap();
+        assert_eq!(current.start, at("2025-06-01T10:00:00Z"));
+        assert_eq!(status.resets_in_secs, Some(3 * 3600 + 55 * 60));
+        assert_eq!(status.tokens_per_minute, 10.0);
+        assert_eq!(status.projected_tokens, 600 + 10 * 235);
+        assert_eq!(status.token_limit, Some(1000));
+        assert_eq!(status.limit_source, Some(LimitSource::HistoricalMax));
+        assert!(status.will_hit_limit);
+        assert_eq!(status.minutes_until_limit, Some(40.0));
+        assert_eq!(status.recent.len(), 2);
+    }
+
+    #[test]
+    fn test_block_status_without_current_block() {
+        let mut events = vec![event("2025-06-01T01:00:00Z").output(1000)];
+        let blocks = split_blocks(&mut events, &Pricing::default());
+        let status = block_status(blocks, Some(5000), at("2025-06-02T12:00:00Z"));
+
+        assert!(status.current.is_none());
+        assert!(status.resets_in_secs.is_none());
+        assert!(!status.will_hit_limit);
+        assert_eq!(status.limit_source, Some(LimitSource::Config));
+    }
+}
@@ -0,0 +1,430 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::analytics_db;
+use super::backups;
+use super::config;
+use super::pricing::{self, CostEstimate, Pricing};
+use super::transcript::TokenUsage;
+use super::usage::{self, DayRange, UsageEvent};
+use super::utils;
+
+const THRESHOLDS: [u8; 2] = [80, 100];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetMetric {
+    Tokens,
+    Cost,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    // Project working directory; the budget is global when absent.
+    #[serde(default)]
+    pub project: Option<String>,
+    pub period: BudgetPeriod,
+    pub metric: BudgetMetric,
+    pub limit: f64,
+}
+
+impl Budget {
+    fn key(&self) -> String {
+        let period = match self.period {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Weekly => "weekly",
+            BudgetPeriod::Monthly => "monthly",
+        };
+        let metric = match self.metric {
+            BudgetMetric::Tokens => "tokens",
+            BudgetMetric::Cost => "cost",
+        };
+        format!(
+            "{}|{}|{}",
+            self.project.as_deref().unwrap_or("*"),
+            period,
+            metric
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.limit.is_finite() || self.limit <= 0.0 {
+            return Err(format!("Budget limit must be positive ({})", self.key()));
+        }
+        if let Some(project) = &self.project {
+            if project.trim().is_empty() {
+                return Err("Budget project cannot be empty".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    pub used: f64,
+    pub fraction: f64,
+    pub period_start: String,
+    pub resets_on: String,
+    // Highest threshold (percent) reached in this period.
+    pub threshold: Option<u8>,
+    // Cost budgets only: usage from models without rates is not counted.
+    pub unpriced_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetAlert {
+    pub status: BudgetStatus,
+    pub threshold: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FiredThreshold {
+    period_start: String,
+    threshold: u8,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BudgetState {
+    #[serde(default)]
+    fired: HashMap<String, FiredThreshold>,
+}
+
+fn state_path() -> Result<PathBuf, String> {
+    Ok(utils::cockpit_dir()?.join("budget-state.json"))
+}
+
+pub fn load_state(path: &Path) -> BudgetState {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_state(path: &Path, state: &BudgetState) -> Result<(), String> {
+    let json_str =
+        serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize: {}", e))?;
+    backups::write_file(path, json_str.as_bytes())
+}
+
+fn period_bounds(period: BudgetPeriod, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match period {
+        BudgetPeriod::Daily => (today, today + Duration::days(1)),
+        BudgetPeriod::Weekly => {
+            let start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (start, start + Duration::days(7))
+        }
+        BudgetPeriod::Monthly => {
+            let start = today.with_day(1).unwrap_or(today);
+            let next = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+            };
+            (start, next.unwrap_or(start))
+        }
+    }
+}
+
+pub fn evaluate(
+    budgets: &[Budget],
+    events: &[UsageEvent],
+    pricing: &Pricing,
+    today: NaiveDate,
+) -> Vec<BudgetStatus> {
+    budgets
+        .iter()
+        .map(|budget| {
+            let (start, next) = period_bounds(budget.period, today);
+            let period_start = start.format("%Y-%m-%d").to_string();
+            let mut tokens = TokenUsage::default();
+            let mut cost = CostEstimate::default();
+            for event in events.iter().filter(|e| {
+                usage::local_day(e.at) >= period_start
+                    && budget.project.as_deref().is_none_or(|p| e.project == p)
+            }) {
+                tokens.add(&event.tokens);
+                cost.add(&event.model, &event.tokens, pricing);
+            }
+
+            let (used, unpriced_models) = match budget.metric {
+                BudgetMetric::Tokens => (tokens.total() as f64, Vec::new()),
+                BudgetMetric::Cost => (cost.cost_usd, cost.unpriced_models),
+            };
+            let fraction = used / budget.limit;
+
+            BudgetStatus {
+                budget: budget.clone(),
+                used,
+                fraction,
+                period_start,
+                resets_on: next.format("%Y-%m-%d").to_string(),
+                threshold: THRESHOLDS
+                    .iter()
+                    .rev()
+                    .find(|t| fraction * 100.0 >= **t as f64)
+                    .copied(),
+                unpriced_models,
+            }
+        })
+        .collect()
+}
+
+// Returns the statuses that reached a threshold not yet reported in their
+// current period, and records them so they are not reported again.
+pub fn new_alerts(state: &mut BudgetState, statuses: &[BudgetStatus]) -> Vec<BudgetAlert> {
+    let mut alerts = Vec::new();
+
+    for status in statuses {
+        let threshold = match status.threshold {
+            Some(t) => t,
+            None => continue,
+        };
+        let key = status.budget.key();
+
+        let already = state
+            .fired
+            .get(&key)
+            .filter(|f| f.period_start == status.period_start)
+            .map(|f| f.threshold)
+            .unwrap_or(0);
+        if threshold <= already {
+            continue;
+        }
+
+        state.fired.insert(
+            key,
+            FiredThreshold {
+                period_start: status.period_start.clone(),
+                threshold,
+            },
+        );
+        alerts.push(BudgetAlert {
+            status: status.clone(),
+            threshold,
+        });
+    }
+
+    alerts
+}
+
+pub struct BudgetCheck {
+    pub statuses: Vec<BudgetStatus>,
+    pub alerts: Vec<BudgetAlert>,
+}
+
+pub fn current_statuses() -> Result<Vec<BudgetStatus>, String> {
+    let config = config::load_config()?;
+    let today = Local::now().date_naive();
+    let earliest = match config
+        .budgets
+        .iter()
+        .map(|b| period_bounds(b.period, today).0)
+        .min()
+    {
+        Some(start) => start.format("%Y-%m-%d").to_string(),
+        None => return Ok(Vec::new()),
+    };
+
+    let range = DayRange::parse(&Some(earliest), &None)?;
+    let events = analytics_db::load_events(range, None)?;
+    Ok(evaluate(
+        &config.budgets,
+        &events,
+        &pricing::current(),
+        today,
+    ))
+}
+
+fn record_alerts(state_path: &Path, statuses: Vec<BudgetStatus>) -> Result<BudgetCheck, String> {
+    let mut state = load_state(state_path);
+    let alerts = new_alerts(&mut state, &statuses);
+    if !alerts.is_empty() {
+        save_state(state_path, &state)?;
+    }
+
+    Ok(BudgetCheck { statuses, alerts })
+}
+
+// Marks new thresholds as fired, so only the watcher, which delivers the
+// alerts, should call this. Readers use `current_statuses`.
+pub fn check() -> Result<BudgetCheck, String> {
+    record_alerts(&state_path()?, current_statuses()?)
+}
+
+pub fn describe(status: &BudgetStatus) -> String {
+    let scope = status
+        .budget
+        .project
+        .as_deref()
+        .map(|p| {
+            Path::new(p)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| p.to_string())
+        })
+        .unwrap_or_else(|| "all projects".to_string());
+    let period = match status.budget.period {
+        BudgetPeriod::Daily => "daily",
+        BudgetPeriod::Weekly => "weekly",
+        BudgetPeriod::Monthly => "monthly",
+    };
+    let amounts = match status.budget.metric {
+        BudgetMetric::Tokens => format!(
+            "{} / {} tokens",
+            status.used as u64, status.budget.limit as u64
+        ),
+        BudgetMetric::Cost => format!("${:.2} / ${:.2}", status.used, status.budget.limit),
+    };
+    format!(
+        "{} {} budget at {:.0}%: {}",
+        scope,
+        period,
+        status.fraction * 100.0,
+        amounts
+    )
+}
+
+#[tauri::command(async)]
+pub fn get_budget_status() -> Result<Vec<BudgetStatus>, String> {
+    current_statuses()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn status(budget: &Budget, period_start: &str, fraction: f64) -> BudgetStatus {
+        BudgetStatus {
+            budget: budget.clone(),
+            used: fraction * budget.limit,
+            fraction,
+            period_start: period_start.to_string(),
+            resets_on: String::new(),
+            threshold: THRESHOLDS
+                .iter()
+                .rev()
+                .find(|t| fraction * 100.0 >= **t as f64)
+                .copied(),
+            unpriced_models: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let today = date("2025-12-18");
+        assert_eq!(
+            period_bounds(BudgetPeriod::Daily, today),
+            (today, date("2025-12-19"))
+        );
+        assert_eq!(
+            period_bounds(BudgetPeriod::Weekly, today),
+            (date("2025-12-15"), date("2025-12-22"))
+        );
+        assert_eq!(
+            period_bounds(BudgetPeriod::Monthly, today),
+            (date("2025-12-01"), date("2026-01-01"))
+        );
+    }
+
+    #[test]
+    fn test_alerts_fire_once_per_threshold_and_period() {
+        let budget = Budget {
+            project: None,
+            period: BudgetPeriod::Daily,
+            metric: BudgetMetric::Cost,
+            limit: 10.0,
+        };
+        let mut state = BudgetState::default();
+
+        assert!(new_alerts(&mut state, &[status(&budget, "2025-06-01", 0.5)]).is_empty());
+
+        let alerts = new_alerts(&mut state, &[status(&budget, "2025-06-01", 0.85)]);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].threshold, 80);
+        assert!(new_alerts(&mut state, &[status(&budget, "2025-06-01", 0.9)]).is_empty());
+
+        let alerts = new_alerts(&mut state, &[status(&budget, "2025-06-01", 1.2)]);
+        assert_eq!(alerts[0].threshold, 100);
+        assert!(new_alerts(&mut state, &[status(&budget, "2025-06-01", 1.5)]).is_empty());
+
+        let alerts = new_alerts(&mut state, &[status(&budget, "2025-06-02", 0.8)]);
+        assert_eq!(alerts[0].threshold, 80);
+    }
+
+    #[test]
+    fn test_state_persists_fired_thresholds() {
+        let path =
+            std::env::temp_dir().join(format!("cockpit-budget-state-{}.json", std::process::id()));
+        let budget = Budget {
+            project: Some("/work/app".to_string()),
+            period: BudgetPeriod::Monthly,
+            metric: BudgetMetric::Tokens,
+            limit: 1000.0,
+        };
+
+        let mut state = BudgetState::default();
+        new_alerts(&mut state, &[status(&budget, "2025-06-01", 1.0)]);
+        save_state(&path, &state).unwrap();
+
+        let mut reloaded = load_state(&path);
+        let _ = fs::remove_file(&path);
+        assert!(new_alerts(&mut reloaded, &[status(&budget, "2025-06-01", 1.1)]).is_empty());
+    }
+
+    #[test]
+    fn test_check_alerts_from_stored_usage() {
+        let dir = std::env::temp_dir().join(format!("cockpit-budget-check-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let transcript = dir.join("projects/-work-app/s1.jsonl");
+        fs::create_dir_all(transcript.parent().unwrap()).unwrap();
+        let lines = [
+            r#"{"type":"user","cwd":"/work/app","timestamp":"2025-06-01T12:00:00Z"}"#,
+            r#"{"type":"assistant","timestamp":"2025-06-01T12:00:05Z","message":{"model":"claude-sonnet-4-5","usage":{"input_tokens":700,"output_tokens":150}}}"#,
+        ];
+        fs::write(&transcript, lines.join("\n") + "\n").unwrap();
+        let state_path = dir.join("budget-state.json");
+        let budgets = [Budget {
+            project: Some("/work/app".to_string()),
+            period: BudgetPeriod::Daily,
+            metric: BudgetMetric::Tokens,
+            limit: 1000.0,
+        }];
+
+        let mut engine = usage::UsageEngine::default();
+        engine.refresh(&dir.join("projects"));
+        let mut db = analytics_db::AnalyticsDb::open_in_memory().unwrap();
+        db.apply(&engine.pending_changes()).unwrap();
+        let events = db.events(DayRange::default(), None).unwrap();
+        let today = date("2025-06-01");
+        let statuses = evaluate(&budgets, &events, &Pricing::default(), today);
+        let untouched = state_path.exists();
+        let first = record_alerts(&state_path, statuses.clone()).unwrap();
+        let second = record_alerts(&state_path, statuses).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(!untouched);
+        assert_eq!(first.statuses[0].used, 850.0);
+        assert_eq!(
+            first.alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(),
+            vec![80]
+        );
+        assert!(second.alerts.is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::analytics_db;
+use super::pricing::{self, Pricing};
+use super::transcript::TokenUsage;
+use super::usage::{self, DayRange, UsageEvent};
+
+// Sessions reading back less from the cache than they send uncached or write
+// to it are flagged, once they are large enough for it to matter.
+const POOR_HIT_RATIO: f64 = 1.0;
+const MIN_PROMPT_TOKENS: u64 = 50_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub key: String,
+    pub messages: u64,
+    pub input_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    // cache_read / (input + cache_creation); None when nothing was sent
+    // uncached.
+    pub hit_ratio: Option<f64>,
+    // Read discount minus the cache write premium, at each model's rates.
+    pub saved_usd: f64,
+    pub unpriced_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionCache {
+    pub project: String,
+    pub poor_reuse: bool,
+    #[serde(flatten)]
+    pub stats: CacheStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheReport {
+    pub total: CacheStats,
+    pub by_project: Vec<CacheStats>,
+    pub by_day: Vec<CacheStats>,
+    pub by_session: Vec<SessionCache>,
+}
+
+#[derive(Default)]
+struct Acc {
+    messages: u64,
+    tokens: TokenUsage,
+    saved_usd: f64,
+    unpriced: BTreeSet<String>,
+}
+
+impl Acc {
+    fn add(&mut self, event: &UsageEvent, pricing: &Pricing) {
+        self.messages += 1;
+        self.tokens.add(&event.tokens);
+        match pricing.rates_for(&event.model) {
+            Some(rates) => {
+                let per_token = |tokens: u64, rate: f64| tokens as f64 * rate / 1_000_000.0;
+                let usage = &event.tokens;
+                self.saved_usd += per_token(
+                    usage.cache_read_input_tokens,
+                    rates.input - rates.cache_read,
+                ) - per_token(
+                    usage.cache_creation_5m_input_tokens(),
+                    rates.cache_write_5m - rates.input,
+                ) - per_token(
+                    usage.cache_creation_1h_input_tokens,
+                    rates.cache_write_1h - rates.input,
+                );
+            }
+            None if event.tokens.total() > 0 => {
+                self.unpriced.insert(event.model.clone());
+            }
+            None => {}
+        }
+    }
+
+    fn prompt_tokens(&self) -> u64 {
+        self.tokens.input_tokens
+            + self.tokens.cache_creation_input_tokens
+            + self.tokens.cache_read_input_tokens
+    }
+
+    fn finish(self, key: String) -> CacheStats {
+        let uncached = self.tokens.input_tokens + self.tokens.cache_creation_input_tokens;
+        CacheStats {
+            key,
+            messages: self.messages,
+            input_tokens: self.tokens.input_tokens,
+            cache_creation_tokens: self.tokens.cache_creation_input_tokens,
+            cache_read_tokens: self.tokens.cache_read_input_tokens,
+            hit_ratio: if uncached > 0 {
+                Some(self.tokens.cache_read_input_tokens as f64 / uncached as f64)
+            } else {
+                None
+            },
+            saved_usd: self.saved_usd,
+            unpriced_models: self.unpriced.into_iter().collect(),
+        }
+    }
+}
+
+pub fn build_report(
+    events: &[UsageEvent],
+    project: Option<&str>,
+    range: DayRange,
+    pricing: &Pricing,
+) -> CacheReport {
+    let mut total = Acc::default();
+    let mut by_project: BTreeMap<String, Acc> = BTreeMap::new();
+    let mut by_day: BTreeMap<String, Acc> = BTreeMap::new();
+    let mut by_session: BTreeMap<(String, String), Acc> = BTreeMap::new();
+
+    for event in events {
+        if !range.contains(event.at) || project.is_some_and(|p| event.project != p) {
+            continue;
+        }
+        total.add(event, pricing);
+        by_project
+            .entry(event.project.clone())
+            .or_default()
+            .add(event, pricing);
+        by_day
+            .entry(usage::local_day(event.at))
+            .or_default()
+            .add(event, pricing);
+        by_session
+            .entry((event.session_id.clone(), event.project.clone()))
+            .or_default()
+            .add(event, pricing);
+    }
+
+    let mut sessions: Vec<SessionCache> = by_session
+        .into_iter()
+        .map(|((session_id, project), acc)| {
+            let large = acc.prompt_tokens() >= MIN_PROMPT_TOKENS;
+            let stats = acc.finish(session_id);
+            SessionCache {
+                project,
+                poor_reuse: large && stats.hit_ratio.is_some_and(|r| r < POOR_HIT_RATIO),
+                stats,
+            }
+        })
+        .collect();
+    sessions.sort_by_key(|s| {
+        (
+            !s.poor_reuse,
+            Reverse(s.stats.input_tokens + s.stats.cache_creation_tokens),
+        )
+    });
+
+    CacheReport {
+        total: total.finish("total".to_string()),
+        by_project: by_project
+            .into_iter()
+            .map(|(key, acc)| acc.finish(key))
+            .collect(),
+        by_day: by_day
+            .into_iter()
+            .map(|(key, acc)| acc.finish(key))
+            .collect(),
+        by_session: sessions,
+    }
+}
+
+#[tauri::command(async)]
+pub fn get_cache_report(
+    project: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<CacheReport, String> {
+    let range = DayRange::parse(&from, &to)?;
+    let events = analytics_db::load_events(range, project.as_deref())?;
+    Ok(build_report(
+        &events,
+        project.as_deref(),
+        range,
+        &pricing::current(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::event;
+
+    #[test]
+    fn test_hit_ratio_and_savings() {
+        let events = vec![
+            event("2025-06-01T12:00:00Z")
+                .input(1000)
+                .cache(1_000_000, 0),
+            event("2025-06-01T12:01:00Z")
+                .input(1000)
+                .cache(0, 1_000_000),
+            event("2025-06-01T12:02:00Z").cache(0, 2_000_000),
+        ];
+        let report = build_report(&events, None, DayRange::default(), &Pricing::default());
+
+        let total = &report.total;
+        assert_eq!(total.messages, 3);
+        assert_eq!(total.hit_ratio, Some(3_000_000.0 / 1_002_000.0));
+        // Reads save 2.7/M; the write premium is 0.75/M.
+        assert!((total.saved_usd - (3.0 * 2.7 - 0.75)).abs() < 1e-9);
+        assert!(total.unpriced_models.is_empty());
+
+        assert_eq!(report.by_day.len(), 1);
+        assert_eq!(report.by_session.len(), 1);
+        assert!(!report.by_session[0].poor_reuse);
+    }
+
+    #[test]
+    fn test_flags_large_sessions_with_poor_reuse() {
+        let events = vec![
+            event("2025-06-01T12:00:00Z")
+                .session("good")
+                .input(10)
+                .cache(60_000, 0),
+            event("2025-06-01T12:01:00Z")
+                .session("good")
+                .input(10)
+                .cache(0, 500_000),
+            event("2025-06-01T13:00:00Z")
+                .session("poor")
+                .input(80_000)
+                .cache(20_000, 10_000),
+            event("2025-06-01T14:00:00Z").session("tiny").input(500),
+            event("2025-06-01T15:00:00Z")
+                .session("other")
+                .model("mystery-model")
+                .cache(0, 100),
+            event("2025-06-01T15:01:00Z")
+                .session("other")
+                .model("<synthetic>"),
+        ];
+        let report = build_report(&events, None, DayRange::default(), &Pricing::default());
+
+        let flagged: Vec<&str> = report
+            .by_session
+            .iter()
+            .filter(|s| s.poor_reuse)
+            .map(|s| s.stats.key.as_str())
+            .collect();
+        assert_eq!(flagged, vec!["poor"]);
+        assert_eq!(report.by_session[0].stats.key, "poor");
+        assert_eq!(report.total.unpriced_models, vec!["mystery-model"]);
+    }
+}
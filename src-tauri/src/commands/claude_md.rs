@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::PathBuf;
 
+use super::backups;
 use super::utils;
 
 fn global_claude_md_path() -> PathBuf {
@@ -54,11 +55,5 @@ pub fn write_claude_md(scope: String, content: String) -> Result<(), String> {
         project_claude_md_write_path(&scope)
     };
 
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
-    }
-
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    backups::write_file(&path, content.as_bytes())
 }
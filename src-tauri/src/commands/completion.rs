@@ -0,0 +1,454 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::pricing::{self, CostEstimate, Pricing};
+use super::transcript::{self, TokenUsage, TranscriptTail, UsageDedup};
+use super::utils;
+
+const STALE_AFTER_MINUTES: i64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionCompleted {
+    pub session_id: String,
+    pub project: String,
+    pub title: String,
+    pub duration_secs: u64,
+    pub tokens: TokenUsage,
+    pub total_tokens: u64,
+    pub cost_usd: Option<f64>,
+    pub model: String,
+    pub final_message_preview: String,
+}
+
+#[derive(Debug, Default)]
+struct SessionState {
+    summary: Option<String>,
+    first_prompt: Option<String>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    usage: TokenUsage,
+    dedup: UsageDedup,
+    cost: CostEstimate,
+    model: String,
+    final_message: String,
+    pending_tools: HashSet<String>,
+    last_stop_reason: Option<String>,
+    turn_open: bool,
+    last_seen: Option<DateTime<Utc>>,
+}
+
+impl SessionState {
+    fn apply(&mut self, entry: &Value, pricing: &Pricing) {
+        if let Some(ts) = transcript::parse_timestamp(entry) {
+            self.first_timestamp.get_or_insert(ts);
+            self.last_timestamp = Some(ts);
+        }
+
+        match transcript::entry_type(entry) {
+            "summary" => {
+                if let Some(summary) = entry.get("summary").and_then(|s| s.as_str()) {
+                    self.summary = Some(summary.to_string());
+                }
+            }
+            "user" => self.apply_user(entry),
+            "assistant" => self.apply_assistant(entry, pricing),
+            _ => {}
+        }
+    }
+
+    fn apply_user(&mut self, entry: &Value) {
+        let mut is_prompt = false;
+
+        match entry.get("message").and_then(|m| m.get("content")) {
+            Some(Value::String(_)) => is_prompt = true,
+            Some(Value::Array(_)) => {
+                for block in transcript::content_blocks(entry) {
+                    match transcript::block_type(block) {
+                        "tool_result" => {
+                            if let Some(id) = block.get("tool_use_id").and_then(|i| i.as_str()) {
+                                self.pending_tools.remove(id);
+                            }
+                        }
+                        "text" => is_prompt = true,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if is_prompt {
+            if self.first_prompt.is_none() {
+                let text = entry
+                    .get("message")
+                    .and_then(|m| m.get("content"))
+                    .map(utils::extract_text_content)
+                    .unwrap_or_default();
+                if !text.is_empty() {
+                    self.first_prompt = Some(text);
+                }
+            }
+            self.turn_open = true;
+            self.last_stop_reason = None;
+        }
+    }
+
+    fn apply_assistant(&mut self, entry: &Value, pricing: &Pricing) {
+        let message = match entry.get("message") {
+            Some(m) => m,
+            None => return,
+        };
+
+        self.turn_open = true;
+
+        for block in transcript::content_blocks(entry) {
+            if transcript::block_type(block) == "tool_use" {
+                if let Some(id) = block.get("id").and_then(|i| i.as_str()) {
+                    self.pending_tools.insert(id.to_string());
+                }
+            }
+        }
+
+        if let Some(content) = message.get("content") {
+            let text = utils::extract_text_content(content);
+            if !text.is_empty() {
+                self.final_message = text;
+            }
+        }
+
+        let model = message.get("model").and_then(|m| m.as_str()).unwrap_or("");
+        if !model.is_empty() {
+            self.model = model.to_string();
+        }
+
+        if let Some(usage) = TokenUsage::from_message(message) {
+            let usage = self.dedup.record(entry, usage).usage;
+            self.usage.add(&usage);
+            self.cost.add(model, &usage, pricing);
+        }
+
+        self.last_stop_reason = message
+            .get("stop_reason")
+            .and_then(|r| r.as_str())
+            .map(|r| r.to_string());
+    }
+
+    fn is_complete(&self) -> bool {
+        self.turn_open
+            && self.pending_tools.is_empty()
+            && self.last_stop_reason.as_deref() == Some("end_turn")
+    }
+}
+
+#[derive(Default)]
+pub struct CompletionTracker {
+    sessions: HashMap<PathBuf, SessionState>,
+    tail: TranscriptTail,
+}
+
+impl CompletionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Applies what was appended to a transcript and reports the session once
+    // per turn, when the last assistant message ended its turn with every tool
+    // call answered. Turns that finished long before cockpit first saw the
+    // file are absorbed silently.
+    pub fn ingest(&mut self, path: &Path, now: DateTime<Utc>) -> Option<SessionCompleted> {
+        let new_lines = match self.tail.read(path) {
+            Some(n) => n,
+            None => {
+                self.forget(path);
+                return None;
+            }
+        };
+        let state = self.sessions.entry(path.to_path_buf()).or_default();
+
+        if new_lines.reset {
+            *state = SessionState::default();
+        }
+        state.last_seen = Some(now);
+
+        let pricing = pricing::current();
+        for entry in &new_lines.lines {
+            state.apply(entry, &pricing);
+        }
+
+        if !state.is_complete() {
+            return None;
+        }
+        state.turn_open = false;
+
+        let last = state.last_timestamp?;
+        if now - last > Duration::minutes(STALE_AFTER_MINUTES) {
+            return None;
+        }
+
+        Some(summarize(path, state))
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.sessions.remove(path);
+        self.tail.forget(path);
+    }
+
+    // Drops sessions with no writes in the stale window. A later write re-reads
+    // the transcript from the start, since new lines alone can't rebuild it.
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let horizon = now - Duration::minutes(STALE_AFTER_MINUTES);
+        let idle: Vec<PathBuf> = self
+            .sessions
+            .iter()
+            .filter(|(_, state)| state.last_seen.is_some_and(|at| at < horizon))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &idle {
+            self.forget(path);
+        }
+    }
+}
+
+fn summarize(path: &Path, state: &SessionState) -> SessionCompleted {
+    let session_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    let project = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| utils::decode_project_name(&n.to_string_lossy()))
+        .unwrap_or_default();
+
+    let title = state
+        .summary
+        .clone()
+        .or_else(|| state.first_prompt.clone())
+        .map(|t| utils::truncate_str(&t, 80))
+        .unwrap_or_else(|| session_id.clone());
+
+    let duration_secs = match (state.first_timestamp, state.last_timestamp) {
+        (Some(first), Some(last)) => (last - first).num_seconds().max(0) as u64,
+        _ => 0,
+    };
+
+    SessionCompleted {
+        session_id,
+        project,
+        title,
+        duration_secs,
+        tokens: state.usage,
+        total_tokens: state.usage.total(),
+        cost_usd: if state.cost.is_complete() {
+            Some(state.cost.cost_usd)
+        } else {
+            None
+        },
+        model: state.model.clone(),
+        final_message_preview: state.final_message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::at;
+    use serde_json::json;
+    use std::fs;
+    use std::io::Write;
+
+    fn session_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("cockpit-completion-{}", std::process::id()))
+            .join("-work-demo");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.jsonl", name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn append(path: &Path, entries: &[Value]) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        for entry in entries {
+            writeln!(file, "{}", entry).unwrap();
+        }
+    }
+
+    fn user_prompt(ts: &str, text: &str) -> Value {
+        json!({"type": "user", "timestamp": ts, "message": {"role": "user", "content": text}})
+    }
+
+    fn assistant(ts: &str, content: Value, stop_reason: Option<&str>) -> Value {
+        json!({
+            "type": "assistant",
+            "timestamp": ts,
+            "message": {
+                "role": "assistant",
+                "model": "claude-sonnet-4-5-20250929",
+                "content": content,
+                "stop_reason": stop_reason,
+                "usage": {"input_tokens": 1000, "output_tokens": 200}
+            }
+        })
+    }
+
+    fn tool_result(ts: &str, id: &str) -> Value {
+        json!({
+            "type": "user",
+            "timestamp": ts,
+            "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": id, "content": "ok"}]}
+        })
+    }
+
+    #[test]
+    fn test_completion_waits_for_pending_tools() {
+        let path = session_file("tools");
+        let mut tracker = CompletionTracker::new();
+
+        append(
+            &path,
+            &[
+                user_prompt("2025-06-01T10:00:00Z", "Fix the failing test"),
+                assistant(
+                    "2025-06-01T10:00:05Z",
+                    json!([{"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {"command": "cargo test"}}]),
+                    Some("tool_use"),
+                ),
+            ],
+        );
+        assert!(tracker.ingest(&path, at("2025-06-01T10:00:06Z")).is_none());
+
+        append(&path, &[tool_result("2025-06-01T10:00:10Z", "tu_1")]);
+        assert!(tracker.ingest(&path, at("2025-06-01T10:00:11Z")).is_none());
+
+        append(
+            &path,
+            &[assistant(
+                "2025-06-01T10:01:00Z",
+                json!([{"type": "text", "text": "All tests pass now."}]),
+                Some("end_turn"),
+            )],
+        );
+        let done = tracker
+            .ingest(&path, at("2025-06-01T10:01:01Z"))
+            .expect("turn should be complete");
+
+        assert_eq!(done.session_id, "tools");
+        assert_eq!(done.project, "demo");
+        assert_eq!(done.title, "Fix the failing test");
+        assert_eq!(done.duration_secs, 60);
+        assert_eq!(done.tokens.input_tokens, 2000);
+        assert_eq!(done.total_tokens, 2400);
+        assert!(done.cost_usd.unwrap() > 0.0);
+        assert_eq!(done.final_message_preview, "All tests pass now.");
+
+        assert!(tracker.ingest(&path, at("2025-06-01T10:01:02Z")).is_none());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_completion_fires_once_per_turn() {
+        let path = session_file("turns");
+        let mut tracker = CompletionTracker::new();
+
+        append(
+            &path,
+            &[
+                user_prompt("2025-06-01T10:00:00Z", "hi"),
+                assistant(
+                    "2025-06-01T10:00:02Z",
+                    json!([{"type": "text", "text": "hello"}]),
+                    Some("end_turn"),
+                ),
+            ],
+        );
+        assert!(tracker.ingest(&path, at("2025-06-01T10:00:03Z")).is_some());
+
+        append(&path, &[json!({"type": "summary", "summary": "Greeting"})]);
+        assert!(tracker.ingest(&path, at("2025-06-01T10:00:04Z")).is_none());
+
+        append(
+            &path,
+            &[
+                user_prompt("2025-06-01T10:05:00Z", "again"),
+                assistant(
+                    "2025-06-01T10:05:02Z",
+                    json!([{"type": "text", "text": "hello again"}]),
+                    Some("end_turn"),
+                ),
+            ],
+        );
+        let done = tracker.ingest(&path, at("2025-06-01T10:05:03Z")).unwrap();
+        assert_eq!(done.title, "Greeting");
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_idle_sessions_are_evicted_and_reread() {
+        let path = session_file("idle");
+        let mut tracker = CompletionTracker::new();
+
+        append(
+            &path,
+            &[
+                user_prompt("2025-06-01T10:00:00Z", "Refactor the parser"),
+                assistant(
+                    "2025-06-01T10:00:02Z",
+                    json!([{"type": "text", "text": "done"}]),
+                    Some("end_turn"),
+                ),
+            ],
+        );
+        assert!(tracker.ingest(&path, at("2025-06-01T10:00:03Z")).is_some());
+        tracker.prune(at("2025-06-01T10:04:00Z"));
+        assert_eq!(tracker.sessions.len(), 1);
+        tracker.prune(at("2025-06-01T10:06:00Z"));
+        assert!(tracker.sessions.is_empty());
+
+        append(
+            &path,
+            &[
+                user_prompt("2025-06-01T10:10:00Z", "And the lexer"),
+                assistant(
+                    "2025-06-01T10:10:02Z",
+                    json!([{"type": "text", "text": "done too"}]),
+                    Some("end_turn"),
+                ),
+            ],
+        );
+        let done = tracker.ingest(&path, at("2025-06-01T10:10:03Z")).unwrap();
+        assert_eq!(done.title, "Refactor the parser");
+        assert_eq!(done.duration_secs, 602);
+        assert_eq!(done.tokens.input_tokens, 2000);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_completion_ignores_stale_turns() {
+        let path = session_file("stale");
+        let mut tracker = CompletionTracker::new();
+
+        append(
+            &path,
+            &[
+                user_prompt("2025-06-01T10:00:00Z", "old"),
+                assistant(
+                    "2025-06-01T10:00:02Z",
+                    json!([{"type": "text", "text": "done"}]),
+                    Some("end_turn"),
+                ),
+            ],
+        );
+        assert!(tracker.ingest(&path, at("2025-06-02T10:00:00Z")).is_none());
+        let _ = fs::remove_file(&path);
+    }
+}
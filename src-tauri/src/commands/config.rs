@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::backups;
+use super::budgets::Budget;
+use super::pricing::{self, ModelRates};
+use super::utils;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CockpitConfig {
+    pub claude_path: String,
+    pub terminal_command: Vec<String>,
+    // Keyed by model id or a substring of it, e.g. "sonnet".
+    pub pricing: BTreeMap<String, ModelRates>,
+    pub budgets: Vec<Budget>,
+    // Tokens allowed per 5-hour block; the busiest past block when unset.
+    pub block_token_limit: Option<u64>,
+}
+
+impl Default for CockpitConfig {
+    fn default() -> Self {
+        CockpitConfig {
+            claude_path: "claude".to_string(),
+            terminal_command: default_terminal_command(),
+            pricing: BTreeMap::new(),
+            budgets: Vec::new(),
+            block_token_limit: None,
+        }
+    }
+}
+
+fn default_terminal_command() -> Vec<String> {
+    let template: &[&str] = if cfg!(target_os = "macos") {
+        // The command line is passed as an argument rather than spliced into
+        // the script, so quotes and backslashes in paths need no escaping.
+        &[
+            "osascript",
+            "-e",
+            "on run argv",
+            "-e",
+            "tell application \"Terminal\" to do script (item 1 of argv)",
+            "-e",
+            "end run",
+            "{command_line}",
+        ]
+    } else if cfg!(target_os = "windows") {
+        &["cmd", "/c", "start", "", "cmd", "/k", "{command}"]
+    } else {
+        &["x-terminal-emulator", "-e", "{command}"]
+    };
+    template.iter().map(|s| s.to_string()).collect()
+}
+
+pub fn config_path() -> Result<PathBuf, String> {
+    Ok(utils::cockpit_dir()?.join("config.json"))
+}
+
+pub fn load_config() -> Result<CockpitConfig, String> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(CockpitConfig::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub fn read_cockpit_config() -> Result<CockpitConfig, String> {
+    load_config()
+}
+
+#[tauri::command]
+pub fn write_cockpit_config(config: CockpitConfig) -> Result<(), String> {
+    if config.claude_path.trim().is_empty() {
+        return Err("Claude binary path cannot be empty".to_string());
+    }
+    if config.terminal_command.is_empty() {
+        return Err("Terminal command cannot be empty".to_string());
+    }
+    for (pattern, rates) in &config.pricing {
+        if pattern.trim().is_empty() {
+            return Err("Pricing override needs a model pattern".to_string());
+        }
+        let values = [
+            rates.input,
+            rates.output,
+            rates.cache_write_5m,
+            rates.cache_write_1h,
+            rates.cache_read,
+        ];
+        if values.iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err(format!("Invalid rates for `{}`", pattern));
+        }
+    }
+    for budget in &config.budgets {
+        budget.validate()?;
+    }
+
+    let json_str =
+        serde_json::to_string_pretty(&config).map_err(|e| format!("Failed to serialize: {}", e))?;
+    backups::write_file(&config_path()?, json_str.as_bytes())?;
+
+    pricing::reload(config.pricing);
+    Ok(())
+}
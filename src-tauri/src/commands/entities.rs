@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use super::backups;
 use super::utils;
 
 fn entity_dir(entity_type: &str, scope: &str) -> std::path::PathBuf {
@@ -161,10 +162,8 @@ pub fn write_entity(
     utils::validate_safe_name(&name)?;
 
     let dir = entity_dir(&entity_type, &scope);
-    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
-
     let path = dir.join(format!("{}.md", name));
-    fs::write(&path, content).map_err(|e| e.to_string())
+    backups::write_file(&path, content.as_bytes())
 }
 
 #[tauri::command]
@@ -0,0 +1,434 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::analytics_db;
+use super::pricing::{self, Pricing};
+use super::transcript::TokenUsage;
+use super::usage::{self, DayRange, UsageEvent};
+use super::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Message,
+    Session,
+    Day,
+    Project,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub granularity: Granularity,
+    // Inclusive local dates, `YYYY-MM-DD`.
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    // Absolute, with the format's extension. Defaults to a dated file in the
+    // downloads directory; an existing file is never replaced.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportResult {
+    pub path: String,
+    pub rows: usize,
+}
+
+const METRIC_COLUMNS: &[&str] = &[
+    "messages",
+    "input_tokens",
+    "output_tokens",
+    "cache_write_5m_tokens",
+    "cache_write_1h_tokens",
+    "cache_read_tokens",
+    "total_tokens",
+    "cost_usd",
+];
+
+#[derive(Debug)]
+struct Row {
+    keys: Vec<(&'static str, String)>,
+    model: String,
+    messages: u64,
+    tokens: TokenUsage,
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+}
+
+impl Row {
+    fn columns(&self, granularity: Granularity) -> Vec<(&'static str, Value)> {
+        let mut columns: Vec<(&'static str, Value)> = self
+            .keys
+            .iter()
+            .map(|(name, value)| (*name, Value::from(value.as_str())))
+            .collect();
+
+        if granularity == Granularity::Session {
+            columns.push(("start", Value::from(self.first.to_rfc3339())));
+            columns.push(("end", Value::from(self.last.to_rfc3339())));
+        }
+        columns.push(("model", Value::from(self.model.as_str())));
+
+        columns
+    }
+
+    fn metrics(&self, pricing: &Pricing) -> Vec<Value> {
+        vec![
+            Value::from(self.messages),
+            Value::from(self.tokens.input_tokens),
+            Value::from(self.tokens.output_tokens),
+            Value::from(self.tokens.cache_creation_5m_input_tokens()),
+            Value::from(self.tokens.cache_creation_1h_input_tokens),
+            Value::from(self.tokens.cache_read_input_tokens),
+            Value::from(self.tokens.total()),
+            // Left empty rather than zero for models without rates.
+            pricing
+                .cost(&self.model, &self.tokens)
+                .map(|c| Value::from((c * 1_000_000.0).round() / 1_000_000.0))
+                .unwrap_or(Value::Null),
+        ]
+    }
+}
+
+fn build_rows(mut events: Vec<UsageEvent>, options: &ExportOptions) -> Result<Vec<Row>, String> {
+    let range = DayRange::parse(&options.from, &options.to)?;
+
+    events.retain(|e| {
+        range.contains(e.at) && options.project.as_ref().is_none_or(|p| &e.project == p)
+    });
+    events.sort_by_key(|e| e.at);
+
+    let mut groups: BTreeMap<(Vec<String>, String), Row> = BTreeMap::new();
+    let mut rows = Vec::new();
+
+    for event in events {
+        let keys: Vec<(&'static str, String)> = match options.granularity {
+            Granularity::Message => vec![
+                ("timestamp", event.at.to_rfc3339()),
+                ("project", event.project.clone()),
+                ("session_id", event.session_id.clone()),
+            ],
+            Granularity::Session => vec![
+                ("session_id", event.session_id.clone()),
+                ("project", event.project.clone()),
+            ],
+            Granularity::Day => vec![("date", usage::local_day(event.at))],
+            Granularity::Project => vec![("project", event.project.clone())],
+        };
+
+        let row = Row {
+            keys,
+            model: event.model.clone(),
+            messages: 1,
+            tokens: event.tokens,
+            first: event.at,
+            last: event.at,
+        };
+
+        if options.granularity == Granularity::Message {
+            rows.push(row);
+            continue;
+        }
+
+        let group_key = (
+            row.keys.iter().map(|(_, v)| v.clone()).collect(),
+            row.model.clone(),
+        );
+        match groups.get_mut(&group_key) {
+            Some(existing) => {
+                existing.messages += 1;
+                existing.tokens.add(&row.tokens);
+                existing.last = row.last;
+            }
+            None => {
+                groups.insert(group_key, row);
+            }
+        }
+    }
+
+    if options.granularity != Granularity::Message {
+        rows = groups.into_values().collect();
+        if options.granularity == Granularity::Session {
+            rows.sort_by_key(|r| r.first);
+        }
+    }
+
+    Ok(rows)
+}
+
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    // Spreadsheets run a cell starting with one of these as a formula.
+    let text = if text.starts_with(['=', '+', '-', '@', '\t', '\r']) && value.is_string() {
+        format!("'{}", text)
+    } else {
+        text
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn key_columns(granularity: Granularity) -> &'static [&'static str] {
+    match granularity {
+        Granularity::Message => &["timestamp", "project", "session_id", "model"],
+        Granularity::Session => &["session_id", "project", "start", "end", "model"],
+        Granularity::Day => &["date", "model"],
+        Granularity::Project => &["project", "model"],
+    }
+}
+
+fn render_csv(rows: &[Row], granularity: Granularity, pricing: &Pricing) -> String {
+    let mut out = key_columns(granularity)
+        .iter()
+        .chain(METRIC_COLUMNS.iter())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(",");
+    out.push('\n');
+
+    for row in rows {
+        let fields: Vec<String> = row
+            .columns(granularity)
+            .into_iter()
+            .map(|(_, v)| v)
+            .chain(row.metrics(pricing))
+            .map(|v| csv_field(&v))
+            .collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn render_jsonl(rows: &[Row], granularity: Granularity, pricing: &Pricing) -> String {
+    let mut out = String::new();
+
+    for row in rows {
+        let mut object = Map::new();
+        for (name, value) in row.columns(granularity) {
+            object.insert(name.to_string(), value);
+        }
+        for (name, value) in METRIC_COLUMNS.iter().zip(row.metrics(pricing)) {
+            object.insert(name.to_string(), value);
+        }
+        out.push_str(&Value::Object(object).to_string());
+        out.push('\n');
+    }
+
+    out
+}
+
+fn default_path(options: &ExportOptions, extension: &str) -> PathBuf {
+    let granularity = match options.granularity {
+        Granularity::Message => "messages",
+        Granularity::Session => "sessions",
+        Granularity::Day => "days",
+        Granularity::Project => "projects",
+    };
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(std::env::temp_dir);
+
+    dir.join(format!(
+        "claude-usage-{}-{}.{}",
+        granularity,
+        Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ))
+}
+
+// Never replaces an existing file.
+fn write_new(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => format!("{} already exists", path.display()),
+            _ => format!("Failed to write {}: {}", path.display(), e),
+        })?;
+    file.write_all(contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[tauri::command(async)]
+pub fn export_usage(options: ExportOptions) -> Result<ExportResult, String> {
+    let range = DayRange::parse(&options.from, &options.to)?;
+    let events = analytics_db::load_events(range, options.project.as_deref())?;
+
+    let rows = build_rows(events, &options)?;
+    let pricing = pricing::current();
+    let content = match options.format {
+        ExportFormat::Csv => render_csv(&rows, options.granularity, &pricing),
+        ExportFormat::Jsonl => render_jsonl(&rows, options.granularity, &pricing),
+    };
+
+    let extension = match options.format {
+        ExportFormat::Csv => "csv",
+        ExportFormat::Jsonl => "jsonl",
+    };
+    let path = match options.path.as_deref().map(str::trim) {
+        Some(p) if !p.is_empty() => utils::validate_export_path(p, extension)?,
+        _ => default_path(&options, extension),
+    };
+    write_new(&path, content.as_bytes())?;
+
+    Ok(ExportResult {
+        path: path.to_string_lossy().to_string(),
+        rows: rows.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::event;
+
+    fn options(granularity: Granularity) -> ExportOptions {
+        ExportOptions {
+            format: ExportFormat::Csv,
+            granularity,
+            from: None,
+            to: None,
+            project: None,
+            path: None,
+        }
+    }
+
+    fn sample() -> Vec<UsageEvent> {
+        vec![
+            event("2025-06-02T12:00:00Z")
+                .session("s2")
+                .project("/work/lib")
+                .input(10)
+                .output(5),
+            event("2025-06-01T12:00:00Z").input(10).output(100),
+            event("2025-06-01T12:30:00Z").input(10).output(50),
+            event("2025-06-01T13:00:00Z")
+                .model("my-local-model")
+                .input(10)
+                .output(7),
+        ]
+    }
+
+    #[test]
+    fn test_project_rows_group_by_model() {
+        let rows = build_rows(sample(), &options(Granularity::Project)).unwrap();
+        let csv = render_csv(&rows, Granularity::Project, &Pricing::default());
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "project,model,messages,input_tokens,output_tokens,cache_write_5m_tokens,\
+             cache_write_1h_tokens,cache_read_tokens,total_tokens,cost_usd"
+        );
+        assert_eq!(
+            lines[1],
+            "/work/app,claude-sonnet-4-5,2,20,150,0,0,0,170,0.00231"
+        );
+        assert_eq!(lines[2], "/work/app,my-local-model,1,10,7,0,0,0,17,");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_filters_by_date_and_project() {
+        let mut opts = options(Granularity::Message);
+        opts.from = Some("2025-06-02".to_string());
+        assert_eq!(build_rows(sample(), &opts).unwrap().len(), 1);
+
+        let mut opts = options(Granularity::Session);
+        opts.project = Some("/work/app".to_string());
+        let rows = build_rows(sample(), &opts).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|r| r.keys[0].1 == "s1"));
+
+        let mut opts = options(Granularity::Day);
+        opts.from = Some("2025-06-03".to_string());
+        opts.to = Some("2025-06-01".to_string());
+        assert!(build_rows(sample(), &opts).is_err());
+
+        opts.to = Some("June".to_string());
+        assert!(build_rows(sample(), &opts)
+            .unwrap_err()
+            .contains("YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn test_jsonl_and_csv_escaping() {
+        let events = vec![event("2025-06-01T12:00:00Z")
+            .project("/work/a, \"b\"")
+            .input(10)
+            .output(1)];
+        let rows = build_rows(events, &options(Granularity::Project)).unwrap();
+
+        let csv = render_csv(&rows, Granularity::Project, &Pricing::default());
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("\"/work/a, \"\"b\"\"\","));
+
+        for (project, expected) in [
+            ("=HYPERLINK(\"x\")", "\"'=HYPERLINK(\"\"x\"\")\","),
+            ("\t=1+1", "'\t=1+1,"),
+            ("\r=1+1", "\"'\r=1+1\","),
+        ] {
+            let events = vec![event("2025-06-01T12:00:00Z")
+                .project(project)
+                .input(10)
+                .output(1)];
+            let formula = build_rows(events, &options(Granularity::Project)).unwrap();
+            let formula_csv = render_csv(&formula, Granularity::Project, &Pricing::default());
+            assert!(formula_csv
+                .split('\n')
+                .nth(1)
+                .unwrap()
+                .starts_with(expected));
+        }
+
+        let jsonl = render_jsonl(&rows, Granularity::Project, &Pricing::default());
+        let parsed: Value = serde_json::from_str(jsonl.trim()).unwrap();
+        assert_eq!(parsed["project"], "/work/a, \"b\"");
+        assert_eq!(parsed["total_tokens"], 11);
+    }
+
+    #[test]
+    fn test_write_new_refuses_to_overwrite() {
+        let path = std::env::temp_dir().join(format!("cockpit-export-{}.csv", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let first = write_new(&path, b"first");
+        let second = write_new(&path, b"second");
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert!(first.is_ok());
+        assert!(second.unwrap_err().contains("already exists"));
+        assert_eq!(contents, "first");
+    }
+}
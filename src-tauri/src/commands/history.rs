@@ -4,6 +4,7 @@ use std::fs;
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 
+use super::analytics_db;
 use super::utils;
 
 #[derive(Debug, Serialize)]
@@ -281,6 +282,7 @@ fn remove_session(path: &Path) -> Result<(), String> {
 #[tauri::command]
 pub fn delete_conversation(session_path: String) -> Result<(), String> {
     let path = utils::validate_session_path(&session_path)?;
+    analytics_db::sync_before_delete();
     remove_session(&path)
 }
 
@@ -351,6 +353,7 @@ pub fn clear_command_history() -> Result<(), String> {
 
 #[tauri::command]
 pub fn clear_all_conversations(project_filter: Option<String>) -> Result<u32, String> {
+    analytics_db::sync_before_delete();
     let project_dirs = utils::list_project_dirs()?;
     let mut count: u32 = 0;
 
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::analytics_db;
+use super::usage::{DayRange, FileEditEvent};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileHotspot {
+    // Relative to the project when the file is inside it.
+    pub path: String,
+    pub edits: u64,
+    pub lines_added: u64,
+    pub lines_removed: u64,
+    pub lines_changed: u64,
+    pub sessions: u64,
+    pub last_edited: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotReport {
+    pub project: String,
+    pub edits: u64,
+    pub sessions: u64,
+    pub files: Vec<FileHotspot>,
+}
+
+struct Acc<'a> {
+    edits: u64,
+    lines_added: u64,
+    lines_removed: u64,
+    sessions: HashSet<&'a str>,
+    last_edited: DateTime<Utc>,
+}
+
+fn display_path(file_path: &str, project: &str) -> String {
+    Path::new(file_path)
+        .strip_prefix(project)
+        .ok()
+        .filter(|rel| !rel.as_os_str().is_empty())
+        .map(|rel| rel.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.to_string())
+}
+
+pub fn build_report(
+    edits: &[FileEditEvent],
+    project: &str,
+    range: DayRange,
+    limit: Option<usize>,
+) -> HotspotReport {
+    let edits: Vec<&FileEditEvent> = edits
+        .iter()
+        .filter(|e| e.project == project && range.contains(e.at))
+        .collect();
+
+    let mut by_file: HashMap<&str, Acc> = HashMap::new();
+    for edit in &edits {
+        let acc = by_file.entry(edit.file_path.as_str()).or_insert(Acc {
+            edits: 0,
+            lines_added: 0,
+            lines_removed: 0,
+            sessions: HashSet::new(),
+            last_edited: edit.at,
+        });
+        acc.edits += 1;
+        acc.lines_added += edit.lines_added;
+        acc.lines_removed += edit.lines_removed;
+        acc.sessions.insert(&edit.session_id);
+        acc.last_edited = acc.last_edited.max(edit.at);
+    }
+
+    let mut files: Vec<FileHotspot> = by_file
+        .into_iter()
+        .map(|(file_path, acc)| FileHotspot {
+            path: display_path(file_path, project),
+            edits: acc.edits,
+            lines_added: acc.lines_added,
+            lines_removed: acc.lines_removed,
+            lines_changed: acc.lines_added + acc.lines_removed,
+            sessions: acc.sessions.len() as u64,
+            last_edited: acc.last_edited,
+        })
+        .collect();
+    files.sort_by_key(|f| {
+        (
+            Reverse(f.edits),
+            Reverse(f.lines_changed),
+            Reverse(f.sessions),
+            f.path.clone(),
+        )
+    });
+    if let Some(limit) = limit {
+        files.truncate(limit);
+    }
+
+    HotspotReport {
+        project: project.to_string(),
+        edits: edits.len() as u64,
+        sessions: edits
+            .iter()
+            .map(|e| e.session_id.as_str())
+            .collect::<HashSet<_>>()
+            .len() as u64,
+        files,
+    }
+}
+
+#[tauri::command(async)]
+pub fn get_file_hotspots(
+    project: String,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+) -> Result<HotspotReport, String> {
+    let range = DayRange::parse(&from, &to)?;
+    let edits = analytics_db::load_file_edits(range, Some(&project))?;
+    Ok(build_report(&edits, &project, range, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::at;
+
+    fn edit(ts: &str, session: &str, file: &str, added: u64, removed: u64) -> FileEditEvent {
+        FileEditEvent {
+            at: at(ts),
+            session_id: session.to_string(),
+            project: "/work/app".to_string(),
+            file_path: file.to_string(),
+            lines_added: added,
+            lines_removed: removed,
+        }
+    }
+
+    #[test]
+    fn test_hotspots_rank_by_edits_then_lines() {
+        let mut edits = vec![
+            edit("2025-06-01T12:00:00Z", "s1", "/work/app/src/main.rs", 3, 1),
+            edit("2025-06-01T12:05:00Z", "s1", "/work/app/src/main.rs", 1, 1),
+            edit("2025-06-02T09:00:00Z", "s2", "/work/app/src/main.rs", 0, 2),
+            edit("2025-06-01T12:01:00Z", "s1", "/work/app/src/lib.rs", 40, 0),
+            edit("2025-06-02T09:01:00Z", "s2", "/work/app/Cargo.toml", 2, 0),
+            edit("2025-06-02T09:02:00Z", "s2", "/tmp/scratch.txt", 5, 0),
+        ];
+        let mut other = edit("2025-06-02T09:00:00Z", "s3", "/work/lib/src/lib.rs", 9, 9);
+        other.project = "/work/lib".to_string();
+        edits.push(other);
+
+        let report = build_report(&edits, "/work/app", DayRange::default(), None);
+        assert_eq!(report.edits, 6);
+        assert_eq!(report.sessions, 2);
+
+        let ranked: Vec<(&str, u64, u64, u64)> = report
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.edits, f.lines_changed, f.sessions))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("src/main.rs", 3, 8, 2),
+                ("src/lib.rs", 1, 40, 1),
+                ("/tmp/scratch.txt", 1, 5, 1),
+                ("Cargo.toml", 1, 2, 1),
+            ]
+        );
+        assert_eq!(report.files[0].last_edited, at("2025-06-02T09:00:00Z"));
+    }
+
+    #[test]
+    fn test_hotspots_filter_range_and_limit() {
+        let edits = vec![
+            edit("2025-06-01T12:00:00Z", "s1", "/work/app/a.rs", 1, 0),
+            edit("2025-06-03T12:00:00Z", "s2", "/work/app/b.rs", 1, 0),
+            edit("2025-06-03T12:00:00Z", "s2", "/work/app/c.rs", 2, 0),
+        ];
+        let range = DayRange::parse(&Some("2025-06-02".to_string()), &None).unwrap();
+
+        let report = build_report(&edits, "/work/app", range, Some(1));
+        assert_eq!(report.edits, 2);
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].path, "c.rs");
+    }
+}
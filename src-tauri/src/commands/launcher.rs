@@ -0,0 +1,313 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::config::{self, CockpitConfig};
+use super::utils;
+
+#[derive(Debug, Serialize)]
+pub struct LaunchResult {
+    pub pid: u32,
+    pub cwd: String,
+    pub command: Vec<String>,
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@".contains(c))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+// `{command}` as a whole argument expands to the claude argv, `{command_line}`
+// to a single shell line that also changes into the working directory (for
+// terminals that do not inherit it), and `{cwd}` to the directory itself.
+pub fn expand_template(template: &[String], cwd: &Path, command: &[String]) -> Vec<String> {
+    let cwd_str = cwd.to_string_lossy().to_string();
+    let command_line = format!(
+        "cd {} && {}",
+        shell_quote(&cwd_str),
+        command
+            .iter()
+            .map(|a| shell_quote(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    let mut argv = Vec::new();
+    for part in template {
+        if part == "{command}" {
+            argv.extend(command.iter().cloned());
+        } else {
+            argv.push(
+                part.replace("{command_line}", &command_line)
+                    .replace("{cwd}", &cwd_str),
+            );
+        }
+    }
+    argv
+}
+
+pub fn launch_in_terminal(
+    config: &CockpitConfig,
+    cwd: &Path,
+    claude_args: &[String],
+) -> Result<LaunchResult, String> {
+    if !cwd.is_dir() {
+        return Err(format!(
+            "Working directory does not exist: {}",
+            cwd.display()
+        ));
+    }
+
+    let mut command = vec![config.claude_path.clone()];
+    command.extend(claude_args.iter().cloned());
+
+    let argv = expand_template(&config.terminal_command, cwd, &command);
+    let (program, args) = argv.split_first().ok_or("Terminal command is empty")?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to launch terminal `{}`: {}", program, e))?;
+
+    // Catch launchers that start but immediately fail (bad flags, no display).
+    std::thread::sleep(Duration::from_millis(300));
+    if let Ok(Some(status)) = child.try_wait() {
+        if !status.success() {
+            return Err(format!("Terminal `{}` exited with {}", program, status));
+        }
+    }
+
+    let pid = child.id();
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    Ok(LaunchResult {
+        pid,
+        cwd: cwd.to_string_lossy().to_string(),
+        command: argv,
+    })
+}
+
+pub fn session_cwd(session_path: &Path) -> Option<PathBuf> {
+    let file = fs::File::open(session_path).ok()?;
+    let reader = BufReader::new(file);
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if let Some(cwd) = parsed.get("cwd").and_then(|c| c.as_str()) {
+            return Some(PathBuf::from(cwd));
+        }
+    }
+
+    None
+}
+
+#[tauri::command(async)]
+pub fn resume_session(session_path: String) -> Result<LaunchResult, String> {
+    let path = utils::validate_session_path(&session_path)?;
+
+    let session_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or("Invalid session file name")?
+        .to_string();
+
+    let cwd = session_cwd(&path).ok_or("Session does not record a working directory")?;
+    let config = config::load_config()?;
+
+    launch_in_terminal(&config, &cwd, &["--resume".to_string(), session_id])
+}
+
+#[tauri::command(async)]
+pub fn launch_session(project_path: String) -> Result<LaunchResult, String> {
+    utils::validate_scope(&project_path)?;
+    if project_path == "global" {
+        return Err("A project directory is required".to_string());
+    }
+
+    let config = config::load_config()?;
+    launch_in_terminal(&config, Path::new(&project_path), &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cockpit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    fn write_stub(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let stub = dir.join("terminal-stub");
+        fs::write(&stub, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+        stub
+    }
+
+    fn config_with(terminal: &[&str]) -> CockpitConfig {
+        CockpitConfig {
+            claude_path: "/opt/claude/bin/claude".to_string(),
+            terminal_command: terminal.iter().map(|s| s.to_string()).collect(),
+            ..CockpitConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_expand_template_splices_command() {
+        let template: Vec<String> = ["term", "--title", "claude: {cwd}", "-e", "{command}"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let command = vec![
+            "claude".to_string(),
+            "--resume".to_string(),
+            "abc".to_string(),
+        ];
+
+        let argv = expand_template(&template, Path::new("/work/app"), &command);
+        assert_eq!(
+            argv,
+            vec![
+                "term",
+                "--title",
+                "claude: /work/app",
+                "-e",
+                "claude",
+                "--resume",
+                "abc"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_template_command_line_quotes() {
+        let template = vec!["osascript".to_string(), "do \"{command_line}\"".to_string()];
+        let command = vec!["claude".to_string()];
+
+        let argv = expand_template(&template, Path::new("/Users/me/it's here"), &command);
+        assert_eq!(argv[1], "do \"cd '/Users/me/it'\\''s here' && claude\"");
+    }
+
+    #[test]
+    fn test_expand_template_command_line_as_argument() {
+        let template: Vec<String> = ["osascript", "-e", "on run argv", "{command_line}"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let command = vec!["claude".to_string()];
+
+        let argv = expand_template(
+            &template,
+            Path::new("/Users/me/say \"hi\" \\ bye"),
+            &command,
+        );
+        assert_eq!(argv.len(), 4);
+        assert_eq!(argv[3], "cd '/Users/me/say \"hi\" \\ bye' && claude");
+    }
+
+    #[test]
+    fn test_launch_rejects_missing_cwd() {
+        let config = config_with(&["true"]);
+        let result = launch_in_terminal(&config, Path::new("/definitely/not/here"), &[]);
+        assert!(result.unwrap_err().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_launch_reports_missing_terminal() {
+        let dir = temp_dir("launch-missing");
+        let config = config_with(&["/definitely/not/a/terminal", "{command}"]);
+        let result = launch_in_terminal(&config, &dir, &[]);
+        let _ = fs::remove_dir_all(&dir);
+        assert!(result.unwrap_err().contains("Failed to launch terminal"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_launch_runs_stub_in_cwd() {
+        let dir = temp_dir("launch-stub");
+        let out = dir.join("out.txt");
+        let stub = write_stub(
+            &dir,
+            &format!("{{ pwd; printf '%s\\n' \"$@\"; }} > '{}'", out.display()),
+        );
+
+        let config = config_with(&[stub.to_str().unwrap(), "-e", "{command}"]);
+        let args = vec!["--resume".to_string(), "session-1".to_string()];
+        let result = launch_in_terminal(&config, &dir, &args).unwrap();
+
+        let written = fs::read_to_string(&out).unwrap_or_default();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.pid > 0);
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                dir.to_str().unwrap(),
+                "-e",
+                "/opt/claude/bin/claude",
+                "--resume",
+                "session-1"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_launch_reports_failing_terminal() {
+        let dir = temp_dir("launch-fail");
+        let stub = write_stub(&dir, "exit 3");
+
+        let config = config_with(&[stub.to_str().unwrap(), "{command}"]);
+        let result = launch_in_terminal(&config, &dir, &[]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.unwrap_err().contains("exited with"));
+    }
+
+    #[test]
+    fn test_session_cwd_reads_first_recorded_cwd() {
+        let dir = temp_dir("session-cwd");
+        let session = dir.join("abc.jsonl");
+        fs::write(
+            &session,
+            "{\"type\":\"summary\",\"summary\":\"x\"}\n{\"type\":\"user\",\"cwd\":\"/work/app\"}\n",
+        )
+        .unwrap();
+
+        let cwd = session_cwd(&session);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(cwd, Some(PathBuf::from("/work/app")));
+    }
+}
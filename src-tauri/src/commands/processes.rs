@@ -0,0 +1,568 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub comm: String,
+    pub cmdline: Vec<String>,
+    pub cwd: PathBuf,
+    pub start_ticks: u64,
+    pub cpu_ticks: u64,
+    pub uptime_secs: u64,
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub open_transcripts: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Signal {
+    Interrupt,
+    Terminate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StopState {
+    Exited,
+    Interrupted,
+    Running,
+}
+
+// How a transcript was paired with its process. Only an open file handle is
+// proof; a heuristic pairing may belong to another session in the project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessMatch {
+    OpenFile,
+    Heuristic,
+}
+
+type CpuSamples = HashMap<(u32, u64), (u64, Instant)>;
+
+static CPU_SAMPLES: OnceLock<Mutex<CpuSamples>> = OnceLock::new();
+
+pub fn proc_root() -> PathBuf {
+    PathBuf::from("/proc")
+}
+
+fn ticks_per_sec() -> u64 {
+    #[cfg(unix)]
+    {
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            return ticks as u64;
+        }
+    }
+    100
+}
+
+fn system_uptime_secs(proc_root: &Path) -> Option<f64> {
+    let content = fs::read_to_string(proc_root.join("uptime")).ok()?;
+    content.split_whitespace().next()?.parse().ok()
+}
+
+struct StatFields {
+    comm: String,
+    state: char,
+    cpu_ticks: u64,
+    start_ticks: u64,
+}
+
+// The comm field is wrapped in parentheses and may itself contain spaces or
+// parentheses, so everything is located relative to the last ')'.
+fn parse_stat(content: &str) -> Option<StatFields> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let comm = content.get(open + 1..close)?.to_string();
+    let rest: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+
+    let state = rest.first()?.chars().next()?;
+    let utime: u64 = rest.get(11)?.parse().ok()?;
+    let stime: u64 = rest.get(12)?.parse().ok()?;
+    let start_ticks: u64 = rest.get(19)?.parse().ok()?;
+
+    Some(StatFields {
+        comm,
+        state,
+        cpu_ticks: utime + stime,
+        start_ticks,
+    })
+}
+
+fn parse_rss_bytes(status: &str) -> u64 {
+    status
+        .lines()
+        .find(|l| l.starts_with("VmRSS:"))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+        .unwrap_or(0)
+}
+
+fn open_transcripts(proc_dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(proc_dir.join("fd")) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .filter_map(|e| fs::read_link(e.path()).ok())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .collect()
+}
+
+// Everything but the open files, which are costly to list for every process.
+fn read_process_info(proc_root: &Path, pid: u32) -> Option<ProcessInfo> {
+    let proc_dir = proc_root.join(pid.to_string());
+
+    let stat = parse_stat(&fs::read_to_string(proc_dir.join("stat")).ok()?)?;
+    if stat.state == 'Z' || stat.state == 'X' {
+        return None;
+    }
+    let cwd = fs::read_link(proc_dir.join("cwd")).ok()?;
+
+    let cmdline = fs::read(proc_dir.join("cmdline"))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|s| !s.is_empty())
+                .map(|s| String::from_utf8_lossy(s).to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let rss_bytes = fs::read_to_string(proc_dir.join("status"))
+        .map(|s| parse_rss_bytes(&s))
+        .unwrap_or(0);
+
+    let ticks = ticks_per_sec();
+    let started_secs = stat.start_ticks as f64 / ticks as f64;
+    let uptime = system_uptime_secs(proc_root)
+        .map(|up| (up - started_secs).max(0.0))
+        .unwrap_or(0.0);
+
+    let cpu_percent = if uptime > 0.0 {
+        (stat.cpu_ticks as f64 / ticks as f64) / uptime * 100.0
+    } else {
+        0.0
+    };
+
+    Some(ProcessInfo {
+        pid,
+        comm: stat.comm,
+        cmdline,
+        cwd,
+        start_ticks: stat.start_ticks,
+        cpu_ticks: stat.cpu_ticks,
+        uptime_secs: uptime as u64,
+        cpu_percent,
+        rss_bytes,
+        open_transcripts: Vec::new(),
+    })
+}
+
+pub fn read_process(proc_root: &Path, pid: u32) -> Option<ProcessInfo> {
+    let mut info = read_process_info(proc_root, pid)?;
+    info.open_transcripts = open_transcripts(&proc_root.join(pid.to_string()));
+    Some(info)
+}
+
+pub fn list_processes<F>(proc_root: &Path, filter: F) -> Vec<ProcessInfo>
+where
+    F: Fn(&ProcessInfo) -> bool,
+{
+    let entries = match fs::read_dir(proc_root) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| read_process_info(proc_root, pid))
+        .filter(|p| filter(p))
+        .map(|mut p| {
+            p.open_transcripts = open_transcripts(&proc_root.join(p.pid.to_string()));
+            p
+        })
+        .collect()
+}
+
+fn file_name_of(arg: &str) -> &str {
+    arg.rsplit(['/', '\\']).next().unwrap_or(arg)
+}
+
+pub fn is_claude_process(info: &ProcessInfo) -> bool {
+    if info.comm == "claude" {
+        return true;
+    }
+
+    let mut args = info.cmdline.iter();
+    let program = match args.next() {
+        Some(p) => file_name_of(p),
+        None => return false,
+    };
+
+    if program == "claude" {
+        return true;
+    }
+
+    if program == "node" || program == "bun" {
+        return args
+            .take(2)
+            .any(|arg| file_name_of(arg) == "claude" || arg.contains("@anthropic-ai/claude-code"));
+    }
+
+    false
+}
+
+// Replaces the lifetime CPU average from /proc with the usage since the
+// previous sample of the same process, so polling callers see current load.
+fn sample_cpu(processes: &mut [ProcessInfo]) {
+    let samples = CPU_SAMPLES.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut samples = match samples.lock() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let now = Instant::now();
+    let ticks = ticks_per_sec() as f64;
+
+    for process in processes.iter_mut() {
+        let key = (process.pid, process.start_ticks);
+        if let Some((prev_ticks, prev_at)) = samples.get(&key) {
+            let elapsed = now.duration_since(*prev_at).as_secs_f64();
+            if elapsed > 0.0 {
+                let used = process.cpu_ticks.saturating_sub(*prev_ticks) as f64 / ticks;
+                process.cpu_percent = used / elapsed * 100.0;
+            }
+        }
+        samples.insert(key, (process.cpu_ticks, now));
+    }
+
+    let live: Vec<(u32, u64)> = processes.iter().map(|p| (p.pid, p.start_ticks)).collect();
+    samples.retain(|key, _| live.contains(key));
+}
+
+pub fn list_claude_processes() -> Vec<ProcessInfo> {
+    if !cfg!(target_os = "linux") {
+        return Vec::new();
+    }
+
+    let mut processes = list_processes(&proc_root(), is_claude_process);
+    sample_cpu(&mut processes);
+    processes
+}
+
+pub fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        let signo = match signal {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+        };
+        let pid = libc::pid_t::try_from(pid).map_err(|_| format!("Invalid pid: {}", pid))?;
+        if unsafe { libc::kill(pid, signo) } != 0 {
+            return Err(format!(
+                "Failed to signal process {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        Err(format!(
+            "Signalling process {} is not supported on this platform",
+            pid
+        ))
+    }
+}
+
+fn current_process(proc_root: &Path, pid: u32, start_ticks: u64) -> Option<ProcessInfo> {
+    read_process(proc_root, pid).filter(|p| p.start_ticks == start_ticks)
+}
+
+// A terminated process must disappear. An interrupted claude keeps running,
+// and a quiet CPU proves nothing since it idles while waiting on the API, so
+// an interrupt only counts once the caller sees the turn end in its transcript.
+pub fn wait_for_stop<F>(
+    proc_root: &Path,
+    process: &ProcessInfo,
+    timeout: Duration,
+    mut turn_ended: F,
+) -> StopState
+where
+    F: FnMut() -> bool,
+{
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if current_process(proc_root, process.pid, process.start_ticks).is_none() {
+            return StopState::Exited;
+        }
+        if turn_ended() {
+            return StopState::Interrupted;
+        }
+        if Instant::now() >= deadline {
+            return StopState::Running;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+// Pairs processes with the transcripts of one project directory. A process
+// holding the transcript open is a definitive match; the rest are paired
+// newest process to most recently written transcript and marked heuristic.
+pub fn match_transcripts(
+    processes: &[ProcessInfo],
+    transcripts: &[(PathBuf, u64)],
+) -> HashMap<PathBuf, (ProcessInfo, ProcessMatch)> {
+    let mut matched: HashMap<PathBuf, (ProcessInfo, ProcessMatch)> = HashMap::new();
+    let mut unmatched: Vec<&ProcessInfo> = Vec::new();
+
+    for process in processes {
+        let open = transcripts.iter().find(|(path, _)| {
+            !matched.contains_key(path) && process.open_transcripts.iter().any(|p| p == path)
+        });
+        match open {
+            Some((path, _)) => {
+                matched.insert(path.clone(), (process.clone(), ProcessMatch::OpenFile));
+            }
+            None => unmatched.push(process),
+        }
+    }
+
+    unmatched.sort_by_key(|p| Reverse(p.start_ticks));
+
+    let mut remaining: Vec<&(PathBuf, u64)> = transcripts
+        .iter()
+        .filter(|(path, _)| !matched.contains_key(path))
+        .collect();
+    remaining.sort_by_key(|(_, modified)| Reverse(*modified));
+
+    for (process, (path, _)) in unmatched.into_iter().zip(remaining) {
+        matched.insert(path.clone(), (process.clone(), ProcessMatch::Heuristic));
+    }
+
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    fn fake_process(pid: u32, comm: &str, cmdline: &[&str], start_ticks: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            comm: comm.to_string(),
+            cmdline: cmdline.iter().map(|s| s.to_string()).collect(),
+            cwd: PathBuf::from("/work/project"),
+            start_ticks,
+            cpu_ticks: 0,
+            uptime_secs: 0,
+            cpu_percent: 0.0,
+            rss_bytes: 0,
+            open_transcripts: Vec::new(),
+        }
+    }
+
+    fn spawn_stand_in(cwd: &Path) -> Child {
+        Command::new("sleep")
+            .arg("30")
+            .current_dir(cwd)
+            .spawn()
+            .expect("failed to spawn stand-in process")
+    }
+
+    #[test]
+    fn test_parse_stat_handles_parens_in_comm() {
+        let stat = "4242 (weird) name) S 1 4242 4242 0 -1 4194560 100 0 0 0 250 50 0 0 20 0 1 0 98765 1000 200";
+        let fields = parse_stat(stat).unwrap();
+        assert_eq!(fields.comm, "weird) name");
+        assert_eq!(fields.state, 'S');
+        assert_eq!(fields.cpu_ticks, 300);
+        assert_eq!(fields.start_ticks, 98765);
+    }
+
+    #[test]
+    fn test_parse_rss_bytes() {
+        let status = "Name:\tclaude\nVmPeak:\t  900 kB\nVmRSS:\t  2048 kB\n";
+        assert_eq!(parse_rss_bytes(status), 2048 * 1024);
+        assert_eq!(parse_rss_bytes("Name:\tkthreadd\n"), 0);
+    }
+
+    #[test]
+    fn test_is_claude_process() {
+        assert!(is_claude_process(&fake_process(
+            1,
+            "claude",
+            &["claude"],
+            0
+        )));
+        assert!(is_claude_process(&fake_process(
+            2,
+            "node",
+            &["node", "/usr/local/bin/claude", "--resume", "abc"],
+            0
+        )));
+        assert!(is_claude_process(&fake_process(
+            3,
+            "node",
+            &[
+                "/usr/bin/node",
+                "/opt/lib/node_modules/@anthropic-ai/claude-code/cli.js"
+            ],
+            0
+        )));
+        assert!(!is_claude_process(&fake_process(
+            4,
+            "node",
+            &["node", "server.js"],
+            0
+        )));
+        assert!(!is_claude_process(&fake_process(
+            5,
+            "vim",
+            &["vim", "claude"],
+            0
+        )));
+    }
+
+    #[test]
+    fn test_match_transcripts_prefers_open_file() {
+        let old = PathBuf::from("/p/old.jsonl");
+        let new = PathBuf::from("/p/new.jsonl");
+        let transcripts = vec![(old.clone(), 100), (new.clone(), 200)];
+
+        let mut holder = fake_process(10, "claude", &["claude"], 1);
+        holder.open_transcripts = vec![old.clone()];
+        let other = fake_process(11, "claude", &["claude"], 2);
+
+        let matched = match_transcripts(&[holder, other], &transcripts);
+        assert_eq!(
+            matched.get(&old).map(|(p, m)| (p.pid, *m)),
+            Some((10, ProcessMatch::OpenFile))
+        );
+        assert_eq!(
+            matched.get(&new).map(|(p, m)| (p.pid, *m)),
+            Some((11, ProcessMatch::Heuristic))
+        );
+    }
+
+    #[test]
+    fn test_match_transcripts_pairs_by_recency() {
+        let a = PathBuf::from("/p/a.jsonl");
+        let b = PathBuf::from("/p/b.jsonl");
+        let c = PathBuf::from("/p/c.jsonl");
+        let transcripts = vec![(a.clone(), 300), (b.clone(), 100), (c.clone(), 200)];
+
+        let older = fake_process(20, "claude", &["claude"], 5);
+        let newer = fake_process(21, "claude", &["claude"], 9);
+
+        let matched = match_transcripts(&[older, newer], &transcripts);
+        assert_eq!(matched.len(), 2);
+        assert_eq!(matched.get(&a).map(|(p, _)| p.pid), Some(21));
+        assert_eq!(matched.get(&c).map(|(p, _)| p.pid), Some(20));
+        assert!(matched.values().all(|(_, m)| *m == ProcessMatch::Heuristic));
+        assert!(!matched.contains_key(&b));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_process_reports_stand_in() {
+        let dir = std::env::temp_dir().join(format!("cockpit-proc-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut child = spawn_stand_in(&dir);
+
+        let info = read_process(&proc_root(), child.id());
+        let _ = child.kill();
+        let _ = child.wait();
+        let _ = fs::remove_dir_all(&dir);
+
+        let info = info.expect("stand-in process should be readable");
+        assert_eq!(info.pid, child.id());
+        assert_eq!(info.comm, "sleep");
+        assert_eq!(info.cwd, dir.canonicalize().unwrap_or(dir));
+        assert!(info.rss_bytes > 0);
+        assert!(info.uptime_secs < 60);
+        assert!(!is_claude_process(&info));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_list_processes_filters_stand_in() {
+        let dir = std::env::temp_dir().join(format!("cockpit-list-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut child = spawn_stand_in(&dir);
+        let pid = child.id();
+
+        let found = list_processes(&proc_root(), |p| p.pid == pid);
+        let _ = child.kill();
+        let _ = child.wait();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(found.len(), 1);
+        assert!(read_process(&proc_root(), pid).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_terminate_stops_stand_in() {
+        let mut child = spawn_stand_in(&std::env::temp_dir());
+        let info = read_process(&proc_root(), child.id()).unwrap();
+
+        send_signal(info.pid, Signal::Terminate).unwrap();
+        let state = wait_for_stop(&proc_root(), &info, Duration::from_secs(5), || false);
+        let _ = child.wait();
+
+        assert_eq!(state, StopState::Exited);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_interrupt_unverified_until_turn_ends() {
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' INT; sleep 30"])
+            .spawn()
+            .expect("failed to spawn stand-in process");
+        std::thread::sleep(Duration::from_millis(100));
+        let info = read_process(&proc_root(), child.id()).unwrap();
+
+        send_signal(info.pid, Signal::Interrupt).unwrap();
+        // Ignoring the signal leaves the process idle, which is not a stop.
+        let ignored = wait_for_stop(&proc_root(), &info, Duration::from_secs(1), || false);
+        let mut polls = 0;
+        let ended = wait_for_stop(&proc_root(), &info, Duration::from_secs(5), || {
+            polls += 1;
+            polls > 2
+        });
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert_eq!(ignored, StopState::Running);
+        assert_eq!(ended, StopState::Interrupted);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_process_ignores_zombies() {
+        let mut child = spawn_stand_in(&std::env::temp_dir());
+        let pid = child.id();
+        send_signal(pid, Signal::Terminate).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        assert!(read_process(&proc_root(), pid).is_none());
+        let _ = child.wait();
+    }
+}
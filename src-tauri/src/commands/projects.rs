@@ -2,6 +2,7 @@ use serde::Serialize;
 use std::fs;
 use std::path::PathBuf;
 
+use super::analytics_db;
 use super::utils;
 
 #[derive(Debug, Serialize)]
@@ -66,6 +67,7 @@ pub fn delete_project(encoded_path: String) -> Result<(), String> {
     }
 
     let canonical = utils::validate_within_projects_dir(&project_dir)?;
+    analytics_db::sync_before_delete();
     fs::remove_dir_all(&canonical).map_err(|e| e.to_string())
 }
 
@@ -1,11 +1,18 @@
 use serde::Serialize;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
-use std::io::{BufRead, BufReader};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use super::processes::{self, ProcessInfo, ProcessMatch, Signal, StopState};
+use super::transcript::{TokenUsage, UsageDedup};
 use super::utils;
 
+// Claude Code records an interrupted turn as a user message carrying this.
+const INTERRUPT_MARKER: &str = "[Request interrupted by user";
+
 #[derive(Debug, Serialize)]
 pub struct ActiveSession {
     pub session_id: String,
@@ -15,6 +22,12 @@ pub struct ActiveSession {
     pub message_count: u32,
     pub last_message_preview: String,
     pub model: String,
+    pub pid: Option<u32>,
+    pub uptime_secs: Option<u64>,
+    pub cpu_percent: Option<f64>,
+    pub rss_bytes: Option<u64>,
+    pub process_alive: bool,
+    pub process_match: Option<ProcessMatch>,
 }
 
 #[derive(Debug, Serialize)]
@@ -25,6 +38,8 @@ pub struct TailMessage {
     pub model: String,
     pub tokens_in: u64,
     pub tokens_out: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,6 +48,51 @@ pub struct TailResult {
     pub total_lines: u32,
 }
 
+#[derive(Debug, Serialize)]
+pub struct SignalResult {
+    pub pid: u32,
+    pub signal: Signal,
+    pub state: StopState,
+    pub stopped: bool,
+}
+
+fn list_transcripts(project_path: &Path) -> Result<Vec<(PathBuf, u64)>, String> {
+    Ok(utils::list_jsonl_files(project_path)?
+        .into_iter()
+        .filter_map(|entry| {
+            let modified = fs::metadata(entry.path())
+                .ok()?
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some((entry.path(), modified))
+        })
+        .collect())
+}
+
+fn find_session_process(
+    session_path: &Path,
+) -> Result<Option<(ProcessInfo, ProcessMatch)>, String> {
+    let project_path = session_path
+        .parent()
+        .ok_or("Session has no project directory")?;
+    let encoded = project_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let project_processes: Vec<ProcessInfo> = processes::list_claude_processes()
+        .into_iter()
+        .filter(|p| utils::encode_project_path(&p.cwd.to_string_lossy()) == encoded)
+        .collect();
+
+    let transcripts = list_transcripts(project_path)?;
+    let mut matched = processes::match_transcripts(&project_processes, &transcripts);
+    Ok(matched.remove(session_path))
+}
+
 #[tauri::command]
 pub fn list_active_sessions(threshold_secs: Option<u64>) -> Result<Vec<ActiveSession>, String> {
     let project_dirs = utils::list_project_dirs()?;
@@ -43,6 +103,15 @@ pub fn list_active_sessions(threshold_secs: Option<u64>) -> Result<Vec<ActiveSes
         .map_err(|e| e.to_string())?
         .as_secs();
 
+    let mut processes_by_project: HashMap<String, Vec<ProcessInfo>> = HashMap::new();
+    for process in processes::list_claude_processes() {
+        let encoded = utils::encode_project_path(&process.cwd.to_string_lossy());
+        processes_by_project
+            .entry(encoded)
+            .or_default()
+            .push(process);
+    }
+
     let mut sessions = Vec::new();
 
     for project_entry in project_dirs {
@@ -50,23 +119,19 @@ pub fn list_active_sessions(threshold_secs: Option<u64>) -> Result<Vec<ActiveSes
         let project_name = utils::decode_project_name(&project_name_encoded);
         let project_path = project_entry.path();
 
-        let jsonl_files = utils::list_jsonl_files(&project_path)?;
+        let transcripts = list_transcripts(&project_path)?;
 
-        for file_entry in jsonl_files {
-            let file_path = file_entry.path();
-            let metadata = match fs::metadata(&file_path) {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
+        let project_processes = processes_by_project
+            .get(&project_name_encoded)
+            .map(|p| p.as_slice())
+            .unwrap_or(&[]);
+        let matched = processes::match_transcripts(project_processes, &transcripts);
 
-            let modified = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+        for (file_path, modified) in transcripts {
+            let process = matched.get(&file_path).map(|(p, _)| p);
+            let process_match = matched.get(&file_path).map(|(_, m)| *m);
 
-            if now - modified > threshold {
+            if now.saturating_sub(modified) > threshold && process.is_none() {
                 continue;
             }
 
@@ -131,6 +196,12 @@ pub fn list_active_sessions(threshold_secs: Option<u64>) -> Result<Vec<ActiveSes
                 message_count,
                 last_message_preview,
                 model,
+                pid: process.map(|p| p.pid),
+                uptime_secs: process.map(|p| p.uptime_secs),
+                cpu_percent: process.map(|p| p.cpu_percent),
+                rss_bytes: process.map(|p| p.rss_bytes),
+                process_alive: process.is_some(),
+                process_match,
             });
         }
     }
@@ -150,6 +221,7 @@ pub fn tail_session(file_path: String, from_line: u32) -> Result<TailResult, Str
     let reader = BufReader::new(file);
     let mut messages = Vec::new();
     let mut total_lines: u32 = 0;
+    let mut dedup = UsageDedup::new();
 
     for line in reader.lines() {
         let line = match line {
@@ -162,6 +234,15 @@ pub fn tail_session(file_path: String, from_line: u32) -> Result<TailResult, Str
 
         total_lines += 1;
         if total_lines <= from_line {
+            // Earlier records of a streamed response still count towards
+            // dedup, so its usage is not reported again on later lines.
+            if line.contains("\"requestId\"") {
+                if let Ok(parsed) = serde_json::from_str::<Value>(&line) {
+                    if let Some(usage) = parsed.get("message").and_then(TokenUsage::from_message) {
+                        dedup.record(&parsed, usage);
+                    }
+                }
+            }
             continue;
         }
 
@@ -192,6 +273,12 @@ pub fn tail_session(file_path: String, from_line: u32) -> Result<TailResult, Str
             continue;
         }
 
+        let usage = parsed
+            .get("message")
+            .and_then(TokenUsage::from_message)
+            .map(|u| dedup.record(&parsed, u).usage)
+            .unwrap_or_default();
+
         let timestamp = parsed
             .get("timestamp")
             .and_then(|t| t.as_str())
@@ -205,23 +292,15 @@ pub fn tail_session(file_path: String, from_line: u32) -> Result<TailResult, Str
             .unwrap_or("")
             .to_string();
 
-        let usage = parsed.get("message").and_then(|m| m.get("usage"));
-        let tokens_in = usage
-            .and_then(|u| u.get("input_tokens"))
-            .and_then(|t| t.as_u64())
-            .unwrap_or(0);
-        let tokens_out = usage
-            .and_then(|u| u.get("output_tokens"))
-            .and_then(|t| t.as_u64())
-            .unwrap_or(0);
-
         messages.push(TailMessage {
             role,
             content,
             timestamp,
             model,
-            tokens_in,
-            tokens_out,
+            tokens_in: usage.input_tokens,
+            tokens_out: usage.output_tokens,
+            cache_creation_tokens: usage.cache_creation_input_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
         });
     }
 
@@ -230,3 +309,122 @@ pub fn tail_session(file_path: String, from_line: u32) -> Result<TailResult, Str
         total_lines,
     })
 }
+
+// Whether records written after `offset` show the turn ending, either through
+// the interruption marker or a final assistant message.
+fn turn_ended_since(path: &Path, offset: u64) -> bool {
+    let mut file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+    if file.seek(SeekFrom::Start(offset)).is_err() {
+        return false;
+    }
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .any(|line| {
+            let parsed: Value = match serde_json::from_str(&line) {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+            match parsed.get("type").and_then(|t| t.as_str()) {
+                Some("user") => line.contains(INTERRUPT_MARKER),
+                Some("assistant") => {
+                    parsed
+                        .pointer("/message/stop_reason")
+                        .and_then(|r| r.as_str())
+                        == Some("end_turn")
+                }
+                _ => false,
+            }
+        })
+}
+
+fn signal_session(
+    file_path: &str,
+    expected_pid: u32,
+    signal: Signal,
+) -> Result<SignalResult, String> {
+    let path = utils::validate_session_path(file_path)?;
+
+    let (process, process_match) =
+        find_session_process(&path)?.ok_or("No running claude process found for this session")?;
+
+    // A recency guess could point at another session's claude in the project.
+    if process_match == ProcessMatch::Heuristic {
+        return Err(
+            "No claude process holds this transcript open, so its owner can't be confirmed"
+                .to_string(),
+        );
+    }
+
+    if process.pid != expected_pid {
+        return Err(format!(
+            "Session is now owned by process {} (expected {}), refresh and confirm again",
+            process.pid, expected_pid
+        ));
+    }
+
+    let offset = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    processes::send_signal(process.pid, signal)?;
+
+    let state = processes::wait_for_stop(
+        &processes::proc_root(),
+        &process,
+        Duration::from_secs(5),
+        || signal == Signal::Interrupt && turn_ended_since(&path, offset),
+    );
+
+    let stopped = match signal {
+        Signal::Interrupt => state != StopState::Running,
+        Signal::Terminate => state == StopState::Exited,
+    };
+
+    Ok(SignalResult {
+        pid: process.pid,
+        signal,
+        state,
+        stopped,
+    })
+}
+
+#[tauri::command(async)]
+pub fn interrupt_session(file_path: String, expected_pid: u32) -> Result<SignalResult, String> {
+    signal_session(&file_path, expected_pid, Signal::Interrupt)
+}
+
+#[tauri::command(async)]
+pub fn terminate_session(file_path: String, expected_pid: u32) -> Result<SignalResult, String> {
+    signal_session(&file_path, expected_pid, Signal::Terminate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_ended_since_only_reads_new_records() {
+        let path = std::env::temp_dir().join(format!("cockpit-turn-{}.jsonl", std::process::id()));
+        let working = r#"{"type":"assistant","message":{"stop_reason":"tool_use"}}"#;
+        let interrupted = r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"[Request interrupted by user]"}]}}"#;
+        let finished = r#"{"type":"assistant","message":{"stop_reason":"end_turn"}}"#;
+
+        fs::write(&path, format!("{}\n{}\n", finished, working)).unwrap();
+        let offset = fs::metadata(&path).unwrap().len();
+        let before = turn_ended_since(&path, offset);
+        fs::write(
+            &path,
+            format!("{}\n{}\n{}\n", finished, working, interrupted),
+        )
+        .unwrap();
+        let after = turn_ended_since(&path, offset);
+        let from_start = turn_ended_since(&path, 0);
+        let _ = fs::remove_file(&path);
+
+        assert!(!before);
+        assert!(after);
+        assert!(from_start);
+    }
+}
@@ -1,10 +1,57 @@
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use super::backups;
+use super::permission_rules;
+use super::settings_schema::{self, SettingsIssue, Severity};
 use super::utils;
 
-fn settings_path(scope: &str, file_type: &str) -> PathBuf {
+#[derive(Debug, Serialize)]
+pub struct SettingsWriteResult {
+    pub written: bool,
+    pub issues: Vec<SettingsIssue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsSource {
+    GlobalSettings,
+    GlobalLocal,
+    Project,
+    ProjectLocal,
+    Managed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourcedValue {
+    pub source: SettingsSource,
+    // Path of the value in that file, when it differs from the key's path.
+    pub path: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyProvenance {
+    pub path: String,
+    pub source: SettingsSource,
+    pub value: Value,
+    // Lower-precedence values this one replaced, highest precedence first.
+    pub overridden: Vec<SourcedValue>,
+    // Set by managed policy, so no other file can change it.
+    pub locked: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveSettings {
+    pub settings: Value,
+    pub provenance: Vec<KeyProvenance>,
+    pub managed_file: Option<String>,
+}
+
+pub fn settings_path(scope: &str, file_type: &str) -> PathBuf {
     let filename = match file_type {
         "settings_local" => "settings.local.json",
         _ => "settings.json",
@@ -17,13 +64,33 @@ fn settings_path(scope: &str, file_type: &str) -> PathBuf {
     }
 }
 
-fn merge_json(base: &Value, overlay: &Value) -> Value {
+// Arrays Claude Code combines across scopes instead of replacing.
+const MERGED_ARRAYS: &[&str] = &[
+    "permissions.allow",
+    "permissions.deny",
+    "permissions.ask",
+    "permissions.additionalDirectories",
+];
+
+// Permission lists by precedence: a rule in one list is dropped from the lists
+// after it, since Claude Code checks deny before ask before allow.
+const PERMISSION_PRECEDENCE: [&str; 3] = ["deny", "ask", "allow"];
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn merge_at(base: &Value, overlay: &Value, path: &str) -> Value {
     match (base, overlay) {
         (Value::Object(base_map), Value::Object(overlay_map)) => {
             let mut merged = base_map.clone();
             for (key, overlay_val) in overlay_map {
                 let merged_val = if let Some(base_val) = merged.get(key) {
-                    merge_json(base_val, overlay_val)
+                    merge_at(base_val, overlay_val, &child_path(path, key))
                 } else {
                     overlay_val.clone()
                 };
@@ -31,10 +98,411 @@ fn merge_json(base: &Value, overlay: &Value) -> Value {
             }
             Value::Object(merged)
         }
+        (Value::Array(base_items), Value::Array(overlay_items))
+            if MERGED_ARRAYS.contains(&path) =>
+        {
+            let mut merged = base_items.clone();
+            for item in overlay_items {
+                if !merged.contains(item) {
+                    merged.push(item.clone());
+                }
+            }
+            Value::Array(merged)
+        }
         _ => overlay.clone(),
     }
 }
 
+// Read-only policy installed by administrators; it outranks every other file.
+pub fn managed_settings_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
+    } else if cfg!(windows) {
+        PathBuf::from(r"C:\ProgramData\ClaudeCode\managed-settings.json")
+    } else {
+        PathBuf::from("/etc/claude-code/managed-settings.json")
+    }
+}
+
+fn merge_json(base: &Value, overlay: &Value) -> Value {
+    merge_at(base, overlay, "")
+}
+
+// Removes rules shadowed by a higher-precedence list, returning each as
+// (list it was removed from, list that shadows it, rule).
+fn apply_permission_precedence(settings: &mut Value) -> Vec<(&'static str, &'static str, Value)> {
+    let mut removed = Vec::new();
+    let permissions = settings
+        .get_mut("permissions")
+        .and_then(|p| p.as_object_mut());
+    let permissions = match permissions {
+        Some(p) => p,
+        None => return removed,
+    };
+
+    for (i, winner) in PERMISSION_PRECEDENCE.iter().enumerate() {
+        let rules = match permissions.get(*winner).and_then(|r| r.as_array()) {
+            Some(r) => r.clone(),
+            None => continue,
+        };
+        for loser in &PERMISSION_PRECEDENCE[i + 1..] {
+            if let Some(list) = permissions.get_mut(*loser).and_then(|l| l.as_array_mut()) {
+                list.retain(|rule| {
+                    let shadowed = rules.contains(rule);
+                    if shadowed {
+                        removed.push((*loser, *winner, rule.clone()));
+                    }
+                    !shadowed
+                });
+            }
+        }
+    }
+    removed
+}
+
+fn flatten(value: &Value, path: &str, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                flatten(item, &child_path(path, key), out);
+            }
+        }
+        // Combined arrays are tracked per element, each with its own source.
+        Value::Array(items) if MERGED_ARRAYS.contains(&path) => {
+            for item in items {
+                out.push((path.to_string(), item.clone()));
+            }
+        }
+        _ if !path.is_empty() => out.push((path.to_string(), value.clone())),
+        _ => {}
+    }
+}
+
+// Whether setting one path replaces the other: equal, or one nested in the
+// other.
+fn overlaps(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner.len() > outer.len()
+            && inner.starts_with(outer)
+            && inner.as_bytes()[outer.len()] == b'.'
+    };
+    a == b || nested(a, b) || nested(b, a)
+}
+
+// Merges layers given lowest precedence first, recording which layer supplied
+// each leaf of the result and what it replaced. For combined arrays that is
+// each element, and the same rule in lower-precedence permission lists.
+fn merge_layers(layers: &[(SettingsSource, Value)]) -> EffectiveSettings {
+    let mut settings = Value::Object(serde_json::Map::new());
+    let mut leaves: Vec<(SettingsSource, Vec<(String, Value)>)> = Vec::new();
+    for (source, value) in layers {
+        settings = merge_json(&settings, value);
+        let mut flat = Vec::new();
+        flatten(value, "", &mut flat);
+        leaves.push((*source, flat));
+    }
+    let shadowed = apply_permission_precedence(&mut settings);
+
+    let mut merged = Vec::new();
+    flatten(&settings, "", &mut merged);
+
+    let provenance = merged
+        .into_iter()
+        .filter_map(|(path, value)| {
+            let element = MERGED_ARRAYS.contains(&path.as_str());
+            let winner = leaves.iter().rposition(|(_, flat)| {
+                flat.iter()
+                    .any(|(p, v)| *p == path && (!element || *v == value))
+            })?;
+
+            let overridden = if element {
+                let shadowed_paths: Vec<String> = shadowed
+                    .iter()
+                    .filter(|(_, by, rule)| child_path("permissions", by) == path && *rule == value)
+                    .map(|(from, _, _)| child_path("permissions", from))
+                    .collect();
+                leaves
+                    .iter()
+                    .rev()
+                    .flat_map(|(source, flat)| {
+                        flat.iter()
+                            .filter(|(p, v)| shadowed_paths.contains(p) && *v == value)
+                            .map(move |(p, v)| SourcedValue {
+                                source: *source,
+                                path: p.clone(),
+                                value: v.clone(),
+                            })
+                    })
+                    .collect()
+            } else {
+                leaves[..winner]
+                    .iter()
+                    .rev()
+                    .flat_map(|(source, flat)| {
+                        flat.iter()
+                            .filter(|(p, _)| overlaps(p, &path))
+                            .map(move |(p, v)| SourcedValue {
+                                source: *source,
+                                path: p.clone(),
+                                value: v.clone(),
+                            })
+                    })
+                    .collect()
+            };
+
+            let source = leaves[winner].0;
+            Some(KeyProvenance {
+                path,
+                source,
+                value,
+                overridden,
+                locked: source == SettingsSource::Managed,
+            })
+        })
+        .collect();
+
+    EffectiveSettings {
+        settings,
+        provenance,
+        managed_file: None,
+    }
+}
+
+// Edits the managed policy would override: changed leaves it sets to something
+// else, and permission rules it shadows with a higher-precedence list.
+fn managed_conflicts(managed: &Value, current: &Value, content: &Value) -> Vec<SettingsIssue> {
+    let mut locked = Vec::new();
+    flatten(managed, "", &mut locked);
+    if locked.is_empty() {
+        return Vec::new();
+    }
+    let mut existing = Vec::new();
+    flatten(current, "", &mut existing);
+    let mut edited = Vec::new();
+    flatten(content, "", &mut edited);
+
+    edited
+        .into_iter()
+        .filter(|leaf| !existing.contains(leaf))
+        .filter_map(|(path, value)| {
+            let message = if MERGED_ARRAYS.contains(&path.as_str()) {
+                let list = path.trim_start_matches("permissions.");
+                let rank = PERMISSION_PRECEDENCE.iter().position(|l| *l == list)?;
+                let by = PERMISSION_PRECEDENCE[..rank].iter().find(|higher| {
+                    locked.contains(&(child_path("permissions", higher), value.clone()))
+                })?;
+                format!(
+                    "Rule {} is overridden by permissions.{} in managed settings",
+                    value, by
+                )
+            } else {
+                let (locked_path, _) = locked
+                    .iter()
+                    .find(|(p, v)| overlaps(p, &path) && (*p != path || *v != value))?;
+                format!("Locked by managed settings ({})", locked_path)
+            };
+            Some(SettingsIssue {
+                path,
+                severity: Severity::Error,
+                message,
+            })
+        })
+        .collect()
+}
+
+// Formatting of an existing file, so rewriting it only touches the lines
+// whose values changed. Key order is kept by serde_json's preserve_order, and
+// arrays or objects written on one line stay on one line.
+struct JsonStyle {
+    indent: String,
+    crlf: bool,
+    trailing_newline: bool,
+    // Original text of each one-line container, keyed by JSON pointer.
+    inline: HashMap<String, String>,
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+        i += 1;
+    }
+    i
+}
+
+fn string_end(bytes: &[u8], mut i: usize) -> Option<usize> {
+    i += 1;
+    loop {
+        match bytes.get(i)? {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+}
+
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+// Walks the value starting at `i`, recording one-line containers, and returns
+// the index just past it.
+fn scan_inline(
+    text: &str,
+    i: usize,
+    pointer: &str,
+    inline: &mut HashMap<String, String>,
+) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let close = match bytes.get(i)? {
+        b'"' => return string_end(bytes, i),
+        b'{' => b'}',
+        b'[' => b']',
+        _ => {
+            let len = bytes[i..]
+                .iter()
+                .position(|b| b.is_ascii_whitespace() || matches!(b, b',' | b'}' | b']'))
+                .unwrap_or(bytes.len() - i);
+            return Some(i + len);
+        }
+    };
+
+    let mut j = skip_whitespace(bytes, i + 1);
+    let mut index = 0;
+    while bytes.get(j) != Some(&close) {
+        let key = if close == b'}' {
+            let key_end = string_end(bytes, j)?;
+            let key: String = serde_json::from_str(&text[j..key_end]).ok()?;
+            j = skip_whitespace(bytes, key_end);
+            if bytes.get(j) != Some(&b':') {
+                return None;
+            }
+            j = skip_whitespace(bytes, j + 1);
+            key
+        } else {
+            index.to_string()
+        };
+        index += 1;
+        j = scan_inline(text, j, &child_pointer(pointer, &key), inline)?;
+        j = skip_whitespace(bytes, j);
+        if bytes.get(j) == Some(&b',') {
+            j = skip_whitespace(bytes, j + 1);
+        } else if bytes.get(j) != Some(&close) {
+            return None;
+        }
+    }
+
+    // Empty containers render the same either way, so they say nothing
+    // about the file's style.
+    let end = j + 1;
+    if index > 0 && !text[i..end].contains('\n') {
+        inline.insert(pointer.to_string(), text[i..end].to_string());
+    }
+    Some(end)
+}
+
+fn compact(value: &Value) -> String {
+    match value {
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(compact).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(map) => format!(
+            "{{{}}}",
+            map.iter()
+                .map(|(k, v)| format!("{}: {}", Value::from(k.as_str()), compact(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => other.to_string(),
+    }
+}
+
+impl JsonStyle {
+    fn detect(text: &str) -> JsonStyle {
+        let indent = text
+            .lines()
+            .skip(1)
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .find(|ws| !ws.is_empty())
+            .unwrap_or("  ");
+        let mut inline = HashMap::new();
+        let start = skip_whitespace(text.as_bytes(), 0);
+        if scan_inline(text, start, "", &mut inline).is_none() {
+            inline.clear();
+        }
+        JsonStyle {
+            indent: indent.to_string(),
+            crlf: text.contains("\r\n"),
+            trailing_newline: text.ends_with('\n'),
+            inline,
+        }
+    }
+
+    fn write(&self, value: &Value, pointer: &str, depth: usize, out: &mut String) {
+        if let Some(original) = self.inline.get(pointer) {
+            if serde_json::from_str::<Value>(original).ok().as_ref() == Some(value) {
+                out.push_str(original);
+                return;
+            }
+            if value.is_array() || value.is_object() {
+                out.push_str(&compact(value));
+                return;
+            }
+        }
+
+        let (open, close, len) = match value {
+            Value::Array(items) => ('[', ']', items.len()),
+            Value::Object(map) => ('{', '}', map.len()),
+            other => {
+                out.push_str(&other.to_string());
+                return;
+            }
+        };
+        out.push(open);
+        if len > 0 {
+            let inner = self.indent.repeat(depth + 1);
+            let entries: Vec<(String, &Value)> = match value {
+                Value::Array(items) => items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| (i.to_string(), v))
+                    .collect(),
+                Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+                _ => Vec::new(),
+            };
+            for (n, (key, child)) in entries.into_iter().enumerate() {
+                out.push_str(if n == 0 { "\n" } else { ",\n" });
+                out.push_str(&inner);
+                if value.is_object() {
+                    out.push_str(&Value::from(key.as_str()).to_string());
+                    out.push_str(": ");
+                }
+                self.write(child, &child_pointer(pointer, &key), depth + 1, out);
+            }
+            out.push('\n');
+            out.push_str(&self.indent.repeat(depth));
+        }
+        out.push(close);
+    }
+
+    fn render(&self, value: &Value) -> String {
+        let mut out = String::new();
+        self.write(value, "", 0, &mut out);
+        if self.trailing_newline {
+            out.push('\n');
+        }
+        if self.crlf {
+            out = out.replace('\n', "\r\n");
+        }
+        out
+    }
+}
+
+fn write_settings_file(path: &Path, settings: &Value) -> Result<(), String> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let json_str = JsonStyle::detect(&existing).render(settings);
+    backups::write_file(path, json_str.as_bytes())
+}
+
 #[tauri::command]
 pub fn read_settings(scope: String, file_type: String) -> Result<Value, String> {
     utils::validate_scope(&scope)?;
@@ -43,35 +511,73 @@ pub fn read_settings(scope: String, file_type: String) -> Result<Value, String>
 }
 
 #[tauri::command]
-pub fn write_settings(scope: String, file_type: String, content: Value) -> Result<(), String> {
+pub fn validate_settings(content: Value) -> Vec<SettingsIssue> {
+    settings_schema::validate(&content)
+}
+
+// Nothing is written when validation finds errors or the edit would be
+// overridden by managed settings; warnings are returned alongside a
+// successful write.
+#[tauri::command]
+pub fn write_settings(
+    scope: String,
+    file_type: String,
+    content: Value,
+) -> Result<SettingsWriteResult, String> {
     utils::validate_scope(&scope)?;
     let path = settings_path(&scope, &file_type);
 
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+    let mut issues = settings_schema::validate(&content);
+    let managed = utils::read_json_file(&managed_settings_path())?;
+    let current = utils::read_json_file(&path).unwrap_or_default();
+    issues.extend(managed_conflicts(&managed, &current, &content));
+    if settings_schema::has_errors(&issues) {
+        return Ok(SettingsWriteResult {
+            written: false,
+            issues,
+        });
     }
 
-    let json_str = serde_json::to_string_pretty(&content)
-        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    write_settings_file(&path, &content)?;
 
-    fs::write(&path, json_str.as_bytes())
-        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    Ok(SettingsWriteResult {
+        written: true,
+        issues,
+    })
 }
 
 #[tauri::command]
-pub fn get_effective_settings(project_path: String) -> Result<Value, String> {
+pub fn get_effective_settings(project_path: String) -> Result<EffectiveSettings, String> {
     utils::validate_scope(&project_path)?;
-    let global_settings = utils::read_json_file(&settings_path("global", "settings"))?;
-    let global_local = utils::read_json_file(&settings_path("global", "settings_local"))?;
-    let project_settings = utils::read_json_file(&settings_path(&project_path, "settings"))?;
-    let project_local = utils::read_json_file(&settings_path(&project_path, "settings_local"))?;
+    let layers = [
+        (
+            SettingsSource::GlobalSettings,
+            utils::read_json_file(&settings_path("global", "settings"))?,
+        ),
+        (
+            SettingsSource::GlobalLocal,
+            utils::read_json_file(&settings_path("global", "settings_local"))?,
+        ),
+        (
+            SettingsSource::Project,
+            utils::read_json_file(&settings_path(&project_path, "settings"))?,
+        ),
+        (
+            SettingsSource::ProjectLocal,
+            utils::read_json_file(&settings_path(&project_path, "settings_local"))?,
+        ),
+        (
+            SettingsSource::Managed,
+            utils::read_json_file(&managed_settings_path())?,
+        ),
+    ];
 
-    let merged = merge_json(&global_settings, &global_local);
-    let merged = merge_json(&merged, &project_settings);
-    let merged = merge_json(&merged, &project_local);
-
-    Ok(merged)
+    let mut effective = merge_layers(&layers);
+    let managed = managed_settings_path();
+    if managed.exists() {
+        effective.managed_file = Some(managed.to_string_lossy().to_string());
+    }
+    Ok(effective)
 }
 
 #[tauri::command]
@@ -82,8 +588,10 @@ pub fn add_permission(
     permission: String,
 ) -> Result<(), String> {
     utils::validate_scope(&scope)?;
+    permission_rules::parse_rule(&permission)?;
     let path = settings_path(&scope, &file_type);
     let mut settings = utils::read_json_file(&path)?;
+    let original = settings.clone();
 
     let permissions = settings
         .as_object_mut()
@@ -103,16 +611,12 @@ pub fn add_permission(
         list.push(Value::String(permission));
     }
 
-    let json_str = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize: {}", e))?;
-
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    let managed = utils::read_json_file(&managed_settings_path())?;
+    if let Some(conflict) = managed_conflicts(&managed, &original, &settings).first() {
+        return Err(conflict.message.clone());
     }
 
-    fs::write(&path, json_str.as_bytes())
-        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    write_settings_file(&path, &settings)
 }
 
 #[tauri::command]
@@ -134,9 +638,289 @@ pub fn remove_permission(
         }
     }
 
-    let json_str = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    write_settings_file(&path, &settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_provenance_records_source_and_overridden_values() {
+        let layers = [
+            (
+                SettingsSource::GlobalSettings,
+                json!({"model": "opus", "env": {"A": "1", "B": "1"}, "statusLine": {"type": "command", "command": "a"}}),
+            ),
+            (SettingsSource::GlobalLocal, json!({"env": {"B": "2"}})),
+            (
+                SettingsSource::Project,
+                json!({"model": "sonnet", "statusLine": "off"}),
+            ),
+            (SettingsSource::ProjectLocal, json!({})),
+        ];
+        let effective = merge_layers(&layers);
+
+        assert_eq!(effective.settings["env"], json!({"A": "1", "B": "2"}));
+        let find = |path: &str| {
+            effective
+                .provenance
+                .iter()
+                .find(|p| p.path == path)
+                .unwrap()
+        };
+
+        let model = find("model");
+        assert_eq!(model.source, SettingsSource::Project);
+        assert_eq!(model.overridden.len(), 1);
+        assert_eq!(model.overridden[0].source, SettingsSource::GlobalSettings);
+        assert_eq!(model.overridden[0].value, json!("opus"));
+
+        assert_eq!(find("env.A").source, SettingsSource::GlobalSettings);
+        assert!(find("env.A").overridden.is_empty());
+        assert_eq!(find("env.B").source, SettingsSource::GlobalLocal);
+
+        let status = find("statusLine");
+        assert_eq!(status.source, SettingsSource::Project);
+        let mut replaced: Vec<&str> = status.overridden.iter().map(|o| o.path.as_str()).collect();
+        replaced.sort();
+        assert_eq!(replaced, vec!["statusLine.command", "statusLine.type"]);
+    }
+
+    #[test]
+    fn test_permission_arrays_concatenate_and_dedupe() {
+        let global = json!({
+            "permissions": {
+                "allow": ["Bash(npm test)", "Read(./src/**)"],
+                "additionalDirectories": ["../shared"]
+            },
+            "enabledMcpjsonServers": ["github"]
+        });
+        let project = json!({
+            "permissions": {
+                "allow": ["Read(./src/**)", "Bash(cargo test:*)"],
+                "additionalDirectories": ["../docs"]
+            },
+            "enabledMcpjsonServers": ["linear"]
+        });
+
+        let merged = merge_json(&global, &project);
+        assert_eq!(
+            merged["permissions"]["allow"],
+            json!(["Bash(npm test)", "Read(./src/**)", "Bash(cargo test:*)"])
+        );
+        assert_eq!(
+            merged["permissions"]["additionalDirectories"],
+            json!(["../shared", "../docs"])
+        );
+        // Other arrays are still replaced by the higher-precedence file.
+        assert_eq!(merged["enabledMcpjsonServers"], json!(["linear"]));
+    }
+
+    #[test]
+    fn test_deny_takes_precedence() {
+        let layers = [
+            (
+                SettingsSource::GlobalSettings,
+                json!({"permissions": {"allow": ["Bash(rm:*)", "WebFetch"], "ask": ["Bash(git push:*)"]}}),
+            ),
+            (SettingsSource::GlobalLocal, json!({})),
+            (
+                SettingsSource::Project,
+                json!({"permissions": {"deny": ["Bash(rm:*)"], "allow": ["Bash(git push:*)"]}}),
+            ),
+            (
+                SettingsSource::ProjectLocal,
+                json!({"permissions": {"deny": ["Bash(git push:*)"]}}),
+            ),
+        ];
+        let effective = merge_layers(&layers);
+
+        assert_eq!(
+            effective.settings["permissions"],
+            json!({
+                "allow": ["WebFetch"],
+                "ask": [],
+                "deny": ["Bash(rm:*)", "Bash(git push:*)"]
+            })
+        );
+
+        let deny: Vec<&KeyProvenance> = effective
+            .provenance
+            .iter()
+            .filter(|p| p.path == "permissions.deny")
+            .collect();
+        assert_eq!(deny.len(), 2);
+        assert_eq!(deny[0].source, SettingsSource::Project);
+        assert_eq!(deny[0].overridden.len(), 1);
+        assert_eq!(deny[0].overridden[0].path, "permissions.allow");
+        assert_eq!(deny[0].overridden[0].source, SettingsSource::GlobalSettings);
+
+        assert_eq!(deny[1].source, SettingsSource::ProjectLocal);
+        let shadowed: Vec<(&str, SettingsSource)> = deny[1]
+            .overridden
+            .iter()
+            .map(|o| (o.path.as_str(), o.source))
+            .collect();
+        assert_eq!(
+            shadowed,
+            vec![
+                ("permissions.allow", SettingsSource::Project),
+                ("permissions.ask", SettingsSource::GlobalSettings),
+            ]
+        );
+
+        let allow = effective
+            .provenance
+            .iter()
+            .find(|p| p.path == "permissions.allow")
+            .unwrap();
+        assert_eq!(allow.value, json!("WebFetch"));
+        assert!(allow.overridden.is_empty());
+    }
 
-    fs::write(&path, json_str.as_bytes())
-        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    #[test]
+    fn test_managed_layer_locks_keys() {
+        let layers = [
+            (
+                SettingsSource::GlobalSettings,
+                json!({"model": "opus", "permissions": {"allow": ["WebFetch"]}}),
+            ),
+            (SettingsSource::GlobalLocal, json!({})),
+            (SettingsSource::Project, json!({"model": "sonnet"})),
+            (SettingsSource::ProjectLocal, json!({})),
+            (
+                SettingsSource::Managed,
+                json!({"model": "haiku", "permissions": {"deny": ["WebFetch"]}}),
+            ),
+        ];
+        let effective = merge_layers(&layers);
+
+        assert_eq!(effective.settings["model"], json!("haiku"));
+        assert_eq!(effective.settings["permissions"]["allow"], json!([]));
+        let model = effective
+            .provenance
+            .iter()
+            .find(|p| p.path == "model")
+            .unwrap();
+        assert!(model.locked);
+        assert_eq!(model.overridden.len(), 2);
+        assert!(effective.provenance.iter().all(|p| p.locked));
+    }
+
+    #[test]
+    fn test_managed_conflicts_refuse_overridden_edits() {
+        let managed = json!({
+            "model": "haiku",
+            "env": {"PROXY": "http://proxy"},
+            "permissions": {"deny": ["Bash(curl:*)"], "ask": ["Bash(git push:*)"]}
+        });
+        let current = json!({"theme": "dark", "model": "opus"});
+
+        // Unchanged keys and non-conflicting edits are fine.
+        let content = json!({
+            "theme": "light",
+            "model": "opus",
+            "env": {"OTHER": "1"},
+            "permissions": {"deny": ["Bash(curl:*)"], "ask": ["Read"], "additionalDirectories": ["../x"]}
+        });
+        assert!(managed_conflicts(&managed, &current, &content).is_empty());
+
+        let content = json!({
+            "model": "sonnet",
+            "env": {"PROXY": "none"},
+            "permissions": {"allow": ["Bash(curl:*)", "Bash(git push:*)", "Read"]}
+        });
+        let conflicts = managed_conflicts(&managed, &current, &content);
+        let mut paths: Vec<&str> = conflicts.iter().map(|c| c.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "env.PROXY",
+                "model",
+                "permissions.allow",
+                "permissions.allow"
+            ]
+        );
+        assert!(conflicts
+            .iter()
+            .any(|c| c.message.contains("permissions.deny")));
+        assert!(conflicts
+            .iter()
+            .any(|c| c.message.contains("permissions.ask")));
+
+        assert!(managed_conflicts(&json!({}), &current, &content).is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_keeps_order_and_style() {
+        let original = "{\r\n    \"model\": \"opus\",\r\n    \"permissions\": {\r\n        \"allow\": [\r\n            \"Read\"\r\n        ],\r\n        \"deny\": []\r\n    },\r\n    \"env\": {\r\n        \"B\": \"1\",\r\n        \"A\": \"2\"\r\n    }\r\n}\r\n";
+        let style = JsonStyle::detect(original);
+        let mut settings: Value = serde_json::from_str(original).unwrap();
+        assert_eq!(style.render(&settings), original);
+
+        settings["permissions"]["allow"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!("Bash(git status)"));
+        let rewritten = style.render(&settings);
+        let changed: Vec<(&str, &str)> = original
+            .lines()
+            .zip(rewritten.lines())
+            .filter(|(a, b)| a != b)
+            .collect();
+        assert_eq!(rewritten.lines().count(), original.lines().count() + 1);
+        assert_eq!(
+            changed[0],
+            ("            \"Read\"", "            \"Read\",")
+        );
+
+        let tabs = JsonStyle::detect("{\n\t\"a\": 1\n}");
+        assert_eq!(tabs.indent, "\t");
+        assert!(!tabs.trailing_newline);
+        assert_eq!(JsonStyle::detect("").indent, "  ");
+    }
+
+    #[test]
+    fn test_rewrite_keeps_inline_containers() {
+        let original = "{\n  \"permissions\": {\n    \"allow\": [\"Read\", \"Write\"],\n    \"deny\": [\"Bash(rm:*)\"]\n  },\n  \"env\": {\"A\":\"1\"},\n  \"hooks\": {}\n}\n";
+        let style = JsonStyle::detect(original);
+        let mut settings: Value = serde_json::from_str(original).unwrap();
+        assert_eq!(style.render(&settings), original);
+
+        settings["permissions"]["allow"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!("Bash(git status)"));
+        let rewritten = style.render(&settings);
+        let changed: Vec<(&str, &str)> = original
+            .lines()
+            .zip(rewritten.lines())
+            .filter(|(a, b)| a != b)
+            .collect();
+        assert_eq!(
+            changed,
+            vec![(
+                "    \"allow\": [\"Read\", \"Write\"],",
+                "    \"allow\": [\"Read\", \"Write\", \"Bash(git status)\"],"
+            )]
+        );
+        assert_eq!(serde_json::from_str::<Value>(&rewritten).unwrap(), settings);
+
+        settings["hooks"]["Stop"] = json!([]);
+        assert!(style
+            .render(&settings)
+            .contains("  \"hooks\": {\n    \"Stop\": []\n  }\n"));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(overlaps("env", "env"));
+        assert!(overlaps("env", "env.A"));
+        assert!(overlaps("env.A", "env"));
+        assert!(!overlaps("env", "envx"));
+        assert!(!overlaps("env.A", "env.B"));
+    }
 }
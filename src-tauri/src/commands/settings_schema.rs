@@ -0,0 +1,427 @@
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingsIssue {
+    // Dotted path to the field, with `[i]` for array items; empty for the root.
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Any,
+    Bool,
+    String,
+    Integer { min: i64 },
+    Enum(&'static [&'static str]),
+    StringList,
+    StringMap,
+    Object(&'static [Field]),
+    ObjectList(&'static [Field]),
+    // Array whose items are each checked as `Kind`.
+    List(&'static Kind),
+    // Object whose fields depend on the value of its tag key; unknown tags
+    // only warn, since newer Claude Code releases may add variants.
+    Tagged(&'static str, &'static [(&'static str, &'static [Field])]),
+    // Object keyed by a fixed set of names, each holding `Kind`.
+    Keyed(&'static [&'static str], &'static Kind),
+}
+
+struct Field {
+    key: &'static str,
+    kind: Kind,
+    required: bool,
+}
+
+const fn field(key: &'static str, kind: Kind) -> Field {
+    Field {
+        key,
+        kind,
+        required: false,
+    }
+}
+
+const fn required(key: &'static str, kind: Kind) -> Field {
+    Field {
+        key,
+        kind,
+        required: true,
+    }
+}
+
+pub const PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+pub const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+const PERMISSIONS: &[Field] = &[
+    field("allow", Kind::StringList),
+    field("deny", Kind::StringList),
+    field("ask", Kind::StringList),
+    field("additionalDirectories", Kind::StringList),
+    field("defaultMode", Kind::Enum(PERMISSION_MODES)),
+    field("disableBypassPermissionsMode", Kind::Enum(&["disable"])),
+];
+
+const HOOK_COMMAND: &[Field] = &[
+    required("type", Kind::String),
+    required("command", Kind::String),
+    field("timeout", Kind::Integer { min: 1 }),
+];
+
+const HOOK_PROMPT: &[Field] = &[
+    required("type", Kind::String),
+    required("prompt", Kind::String),
+    field("timeout", Kind::Integer { min: 1 }),
+];
+
+const HOOK: Kind = Kind::Tagged(
+    "type",
+    &[("command", HOOK_COMMAND), ("prompt", HOOK_PROMPT)],
+);
+
+const HOOK_MATCHER: &[Field] = &[
+    field("matcher", Kind::String),
+    required("hooks", Kind::List(&HOOK)),
+];
+
+const HOOK_MATCHERS: Kind = Kind::ObjectList(HOOK_MATCHER);
+
+const STATUS_LINE: &[Field] = &[
+    required("type", Kind::Enum(&["command"])),
+    required("command", Kind::String),
+    field("padding", Kind::Integer { min: 0 }),
+];
+
+const SETTINGS: &[Field] = &[
+    field("$schema", Kind::String),
+    field("apiKeyHelper", Kind::String),
+    field("awsAuthRefresh", Kind::String),
+    field("awsCredentialExport", Kind::String),
+    field("otelHeadersHelper", Kind::String),
+    field("cleanupPeriodDays", Kind::Integer { min: 0 }),
+    field("env", Kind::StringMap),
+    field("includeCoAuthoredBy", Kind::Bool),
+    field("permissions", Kind::Object(PERMISSIONS)),
+    field("hooks", Kind::Keyed(HOOK_EVENTS, &HOOK_MATCHERS)),
+    field("disableAllHooks", Kind::Bool),
+    field("model", Kind::String),
+    field("statusLine", Kind::Object(STATUS_LINE)),
+    field("outputStyle", Kind::String),
+    field("forceLoginMethod", Kind::Enum(&["claudeai", "console"])),
+    field("forceLoginOrgUUID", Kind::String),
+    field("enableAllProjectMcpServers", Kind::Bool),
+    field("enabledMcpjsonServers", Kind::StringList),
+    field("disabledMcpjsonServers", Kind::StringList),
+    field("companyAnnouncements", Kind::StringList),
+    field("spinnerTipsEnabled", Kind::Bool),
+    field("alwaysThinkingEnabled", Kind::Bool),
+    field("sandbox", Kind::Any),
+];
+
+struct Validator {
+    issues: Vec<SettingsIssue>,
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+impl Validator {
+    fn push(&mut self, path: &str, severity: Severity, message: String) {
+        self.issues.push(SettingsIssue {
+            path: path.to_string(),
+            severity,
+            message,
+        });
+    }
+
+    fn expected(&mut self, path: &str, expected: &str, value: &Value) {
+        self.push(
+            path,
+            Severity::Error,
+            format!("Expected {}, found {}", expected, type_name(value)),
+        );
+    }
+
+    fn check(&mut self, path: &str, kind: &Kind, value: &Value) {
+        match kind {
+            Kind::Any => {}
+            Kind::Bool => {
+                if !value.is_boolean() {
+                    self.expected(path, "a boolean", value);
+                }
+            }
+            Kind::String => {
+                if !value.is_string() {
+                    self.expected(path, "a string", value);
+                }
+            }
+            Kind::Integer { min } => match value.as_i64() {
+                Some(n) if n >= *min => {}
+                Some(n) => self.push(
+                    path,
+                    Severity::Error,
+                    format!("Must be at least {}, found {}", min, n),
+                ),
+                None => self.expected(path, "an integer", value),
+            },
+            Kind::Enum(allowed) => match value.as_str() {
+                Some(s) if allowed.contains(&s) => {}
+                Some(s) => self.push(
+                    path,
+                    Severity::Error,
+                    format!(
+                        "Unknown value `{}`, expected one of: {}",
+                        s,
+                        allowed.join(", ")
+                    ),
+                ),
+                None => self.expected(path, "a string", value),
+            },
+            Kind::StringList => match value.as_array() {
+                Some(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        if !item.is_string() {
+                            self.expected(&format!("{}[{}]", path, i), "a string", item);
+                        }
+                    }
+                }
+                None => self.expected(path, "an array of strings", value),
+            },
+            Kind::StringMap => match value.as_object() {
+                Some(map) => {
+                    for (key, item) in map {
+                        if !item.is_string() {
+                            self.expected(&join(path, key), "a string", item);
+                        }
+                    }
+                }
+                None => self.expected(path, "an object of strings", value),
+            },
+            Kind::Object(fields) => self.check_object(path, fields, value),
+            Kind::ObjectList(fields) => match value.as_array() {
+                Some(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check_object(&format!("{}[{}]", path, i), fields, item);
+                    }
+                }
+                None => self.expected(path, "an array", value),
+            },
+            Kind::List(inner) => match value.as_array() {
+                Some(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check(&format!("{}[{}]", path, i), inner, item);
+                    }
+                }
+                None => self.expected(path, "an array", value),
+            },
+            Kind::Tagged(tag, variants) => {
+                let tag_value = match value.as_object() {
+                    Some(map) => map.get(*tag),
+                    None => return self.expected(path, "an object", value),
+                };
+                match tag_value {
+                    None => self.push(
+                        &join(path, tag),
+                        Severity::Error,
+                        "Required field is missing".to_string(),
+                    ),
+                    Some(Value::String(s)) => match variants.iter().find(|(name, _)| name == s) {
+                        Some((_, fields)) => self.check_object(path, fields, value),
+                        None => {
+                            let names: Vec<&str> = variants.iter().map(|(name, _)| *name).collect();
+                            self.push(
+                                &join(path, tag),
+                                Severity::Warning,
+                                format!(
+                                    "Unknown {} `{}`, expected one of: {}",
+                                    tag,
+                                    s,
+                                    names.join(", ")
+                                ),
+                            );
+                        }
+                    },
+                    Some(other) => self.expected(&join(path, tag), "a string", other),
+                }
+            }
+            Kind::Keyed(names, inner) => match value.as_object() {
+                Some(map) => {
+                    for (key, item) in map {
+                        let item_path = join(path, key);
+                        if !names.contains(&key.as_str()) {
+                            self.push(
+                                &item_path,
+                                Severity::Warning,
+                                format!("Unknown key `{}`; it will be ignored", key),
+                            );
+                        }
+                        self.check(&item_path, inner, item);
+                    }
+                }
+                None => self.expected(path, "an object", value),
+            },
+        }
+    }
+
+    fn check_object(&mut self, path: &str, fields: &[Field], value: &Value) {
+        let map = match value.as_object() {
+            Some(m) => m,
+            None => return self.expected(path, "an object", value),
+        };
+
+        for field in fields {
+            match map.get(field.key) {
+                Some(item) => self.check(&join(path, field.key), &field.kind, item),
+                None if field.required => self.push(
+                    &join(path, field.key),
+                    Severity::Error,
+                    "Required field is missing".to_string(),
+                ),
+                None => {}
+            }
+        }
+        // Newer Claude Code releases add settings, so unknown keys only warn.
+        for key in map.keys() {
+            if !fields.iter().any(|f| f.key == key) {
+                self.push(
+                    &join(path, key),
+                    Severity::Warning,
+                    format!("Unknown setting `{}`", key),
+                );
+            }
+        }
+    }
+}
+
+pub fn validate(content: &Value) -> Vec<SettingsIssue> {
+    let mut validator = Validator { issues: Vec::new() };
+    validator.check_object("", SETTINGS, content);
+    validator.issues
+}
+
+pub fn has_errors(issues: &[SettingsIssue]) -> bool {
+    issues.iter().any(|i| i.severity == Severity::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(issues: &[SettingsIssue], severity: Severity) -> Vec<&str> {
+        let mut paths: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .map(|i| i.path.as_str())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_valid_settings_have_no_issues() {
+        let settings = json!({
+            "$schema": "https://json.schemastore.org/claude-code-settings.json",
+            "model": "claude-sonnet-4-5",
+            "env": {"FOO": "1"},
+            "permissions": {
+                "allow": ["Bash(npm run test:*)"],
+                "deny": ["Read(./.env)"],
+                "defaultMode": "acceptEdits"
+            },
+            "hooks": {
+                "PostToolUse": [
+                    {"matcher": "Edit|Write", "hooks": [{"type": "command", "command": "cargo fmt", "timeout": 30}]}
+                ],
+                "Stop": [
+                    {"hooks": [{"type": "prompt", "prompt": "Check that the tests pass", "timeout": 30}]}
+                ]
+            },
+            "statusLine": {"type": "command", "command": "~/.claude/statusline.sh", "padding": 0},
+            "cleanupPeriodDays": 30
+        });
+        assert!(validate(&settings).is_empty());
+    }
+
+    #[test]
+    fn test_field_level_errors_and_warnings() {
+        let settings = json!({
+            "permissions": {"defaultMode": "acceptAll", "allow": ["Bash", 3]},
+            "env": {"DEBUG": true},
+            "hooks": {
+                "PreToolUse": [{"hooks": [{"type": "command"}, {"type": "prompt", "command": "x"}]}],
+                "Stop": [{"hooks": [{"type": "webhook", "url": "https://example.com"}]}],
+                "PostToolUze": []
+            },
+            "statusLine": "bash ~/status.sh",
+            "cleanupPeriodDays": -1,
+            "theme": "dark"
+        });
+        let issues = validate(&settings);
+
+        assert_eq!(
+            paths(&issues, Severity::Error),
+            vec![
+                "cleanupPeriodDays",
+                "env.DEBUG",
+                "hooks.PreToolUse[0].hooks[0].command",
+                "hooks.PreToolUse[0].hooks[1].prompt",
+                "permissions.allow[1]",
+                "permissions.defaultMode",
+                "statusLine",
+            ]
+        );
+        assert_eq!(
+            paths(&issues, Severity::Warning),
+            vec![
+                "hooks.PostToolUze",
+                "hooks.PreToolUse[0].hooks[1].command",
+                "hooks.Stop[0].hooks[0].type",
+                "theme"
+            ]
+        );
+        assert!(has_errors(&issues));
+    }
+
+    #[test]
+    fn test_root_must_be_an_object() {
+        let issues = validate(&json!([]));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "");
+    }
+}
@@ -0,0 +1,343 @@
+use chrono::{DateTime, Local, Timelike, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+use super::analytics_db;
+use super::usage::{self, DayRange, ToolCallEvent, UsageEvent};
+use super::utils;
+
+const CANDIDATES: [&str; 2] = ["stats-cache.json", "statsig-cache.json"];
+
+// Newest `version` this model was written against. Newer files are still
+// read; fields that no longer parse are filled from transcripts instead.
+const KNOWN_VERSION: u64 = 2;
+
+// A field of the wrong type is treated as missing rather than failing the
+// whole file.
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DayActivity {
+    pub date: String,
+    pub message_count: u64,
+    pub session_count: u64,
+    pub tool_call_count: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DailyModelTokens {
+    pub date: String,
+    pub tokens_by_model: BTreeMap<String, u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_search_requests: Option<u64>,
+    #[serde(rename = "costUSD", skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LongestSession {
+    pub session_id: String,
+    pub duration: u64,
+    pub message_count: u64,
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsCache {
+    #[serde(default, deserialize_with = "lenient")]
+    pub version: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub last_computed_date: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub daily_activity: Vec<DayActivity>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub daily_model_tokens: Vec<DailyModelTokens>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub model_usage: BTreeMap<String, ModelUsage>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub total_sessions: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub total_messages: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub longest_session: Option<LongestSession>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub first_session_date: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub hour_counts: BTreeMap<String, u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub total_speculation_time_saved_ms: Option<u64>,
+    // Fields added by newer versions, passed through untouched.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsCacheReport {
+    // None when no cache file exists and everything came from transcripts.
+    pub file: Option<String>,
+    pub schema_version: Option<u64>,
+    pub newer_schema: bool,
+    pub stats: StatsCache,
+    // Fields missing or unreadable in the file, filled from transcripts.
+    pub derived_fields: Vec<String>,
+}
+
+pub fn parse(content: &str) -> Result<StatsCache, String> {
+    serde_json::from_str(content).map_err(|e| e.to_string())
+}
+
+// The same figures the cache holds, computed from the usage engine. Messages
+// count API responses, so they run lower than the cache's totals, which also
+// include user turns.
+pub fn derive(events: &[UsageEvent], tool_calls: &[ToolCallEvent]) -> StatsCache {
+    let mut days: BTreeMap<String, DayActivity> = BTreeMap::new();
+    let mut day_sessions: HashMap<String, HashSet<&str>> = HashMap::new();
+    let mut day_models: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+    let mut model_usage: BTreeMap<String, ModelUsage> = BTreeMap::new();
+    let mut session_starts: HashMap<&str, DateTime<Utc>> = HashMap::new();
+
+    for event in events {
+        let day = usage::local_day(event.at);
+        let activity = days.entry(day.clone()).or_default();
+        activity.message_count += 1;
+        day_sessions
+            .entry(day.clone())
+            .or_default()
+            .insert(&event.session_id);
+        *day_models
+            .entry(day)
+            .or_default()
+            .entry(event.model.clone())
+            .or_default() += event.tokens.input_tokens + event.tokens.output_tokens;
+
+        let model = model_usage.entry(event.model.clone()).or_default();
+        model.input_tokens += event.tokens.input_tokens;
+        model.output_tokens += event.tokens.output_tokens;
+        model.cache_read_input_tokens += event.tokens.cache_read_input_tokens;
+        model.cache_creation_input_tokens += event.tokens.cache_creation_input_tokens;
+
+        let start = session_starts.entry(&event.session_id).or_insert(event.at);
+        if event.at < *start {
+            *start = event.at;
+        }
+    }
+    for call in tool_calls {
+        days.entry(usage::local_day(call.at))
+            .or_default()
+            .tool_call_count += 1;
+    }
+    for (day, activity) in days.iter_mut() {
+        activity.date = day.clone();
+        activity.session_count = day_sessions.get(day).map(|s| s.len()).unwrap_or(0) as u64;
+    }
+
+    let mut hour_counts: BTreeMap<String, u64> = BTreeMap::new();
+    for start in session_starts.values() {
+        let hour = start.with_timezone(&Local).hour();
+        *hour_counts.entry(hour.to_string()).or_default() += 1;
+    }
+
+    StatsCache {
+        first_session_date: session_starts.values().min().map(|at| at.to_rfc3339()),
+        total_sessions: Some(session_starts.len() as u64),
+        total_messages: Some(events.len() as u64),
+        daily_activity: days.into_values().collect(),
+        daily_model_tokens: day_models
+            .into_iter()
+            .map(|(date, tokens_by_model)| DailyModelTokens {
+                date,
+                tokens_by_model,
+                extra: Map::new(),
+            })
+            .collect(),
+        model_usage,
+        hour_counts,
+        ..StatsCache::default()
+    }
+}
+
+// Fills fields the file did not provide from `derived`, returning their names.
+pub fn fill_missing(stats: &mut StatsCache, derived: StatsCache) -> Vec<String> {
+    let mut filled = Vec::new();
+
+    if stats.daily_activity.is_empty() && !derived.daily_activity.is_empty() {
+        stats.daily_activity = derived.daily_activity;
+        filled.push("dailyActivity");
+    }
+    if stats.daily_model_tokens.is_empty() && !derived.daily_model_tokens.is_empty() {
+        stats.daily_model_tokens = derived.daily_model_tokens;
+        filled.push("dailyModelTokens");
+    }
+    if stats.model_usage.is_empty() && !derived.model_usage.is_empty() {
+        stats.model_usage = derived.model_usage;
+        filled.push("modelUsage");
+    }
+    if stats.hour_counts.is_empty() && !derived.hour_counts.is_empty() {
+        stats.hour_counts = derived.hour_counts;
+        filled.push("hourCounts");
+    }
+    if stats.total_sessions.is_none() {
+        stats.total_sessions = derived.total_sessions;
+        filled.push("totalSessions");
+    }
+    if stats.total_messages.is_none() {
+        stats.total_messages = derived.total_messages;
+        filled.push("totalMessages");
+    }
+    if stats.first_session_date.is_none() && derived.first_session_date.is_some() {
+        stats.first_session_date = derived.first_session_date;
+        filled.push("firstSessionDate");
+    }
+
+    filled.into_iter().map(String::from).collect()
+}
+
+#[tauri::command(async)]
+pub fn read_stats_cache() -> Result<StatsCacheReport, String> {
+    let base = utils::claude_dir();
+
+    let mut file = None;
+    let mut stats = StatsCache::default();
+    for name in &CANDIDATES {
+        let path = base.join(name);
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            stats = parse(&content).map_err(|e| format!("Failed to parse {}: {}", name, e))?;
+            file = Some(name.to_string());
+            break;
+        }
+    }
+
+    let derived = derive(
+        &analytics_db::load_events(DayRange::default(), None)?,
+        &analytics_db::load_tool_calls(DayRange::default(), None)?,
+    );
+    let derived_fields = fill_missing(&mut stats, derived);
+
+    Ok(StatsCacheReport {
+        file,
+        schema_version: stats.version,
+        newer_schema: stats.version.is_some_and(|v| v > KNOWN_VERSION),
+        stats,
+        derived_fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::{at, event};
+    use serde_json::json;
+
+    #[test]
+    fn test_unknown_fields_round_trip() {
+        let raw = json!({
+            "version": 3,
+            "lastComputedDate": "2025-06-02",
+            "dailyActivity": [
+                {"date": "2025-06-01", "messageCount": 12, "sessionCount": 2, "toolCallCount": 5, "linesAdded": 40}
+            ],
+            "modelUsage": {
+                "claude-sonnet-4-5": {"inputTokens": 10, "outputTokens": 20, "costUSD": 0.5, "reasoningTokens": 7}
+            },
+            "totalSessions": 2,
+            "totalMessages": 12,
+            "hourCounts": {"9": 1, "14": 1},
+            "streaks": {"longest": 4}
+        });
+
+        let stats = parse(&raw.to_string()).unwrap();
+        assert_eq!(stats.version, Some(3));
+        assert_eq!(stats.daily_activity[0].message_count, 12);
+        assert_eq!(stats.daily_activity[0].extra["linesAdded"], 40);
+        assert_eq!(stats.model_usage["claude-sonnet-4-5"].cost_usd, Some(0.5));
+        assert_eq!(stats.extra["streaks"], json!({"longest": 4}));
+
+        let back = serde_json::to_value(&stats).unwrap();
+        assert_eq!(back["streaks"], raw["streaks"]);
+        assert_eq!(
+            back["modelUsage"]["claude-sonnet-4-5"]["reasoningTokens"],
+            7
+        );
+        assert_eq!(back["dailyActivity"][0]["linesAdded"], 40);
+    }
+
+    #[test]
+    fn test_mistyped_fields_fall_back_to_transcripts() {
+        let raw = json!({
+            "version": 2,
+            "dailyActivity": "not a list",
+            "totalMessages": 99
+        });
+        let mut stats = parse(&raw.to_string()).unwrap();
+        assert!(stats.daily_activity.is_empty());
+
+        let events = vec![
+            event("2025-06-01T12:00:00Z").input(10).output(5),
+            event("2025-06-01T12:30:00Z").input(10).output(5),
+            event("2025-06-01T13:00:00Z")
+                .session("s2")
+                .input(10)
+                .output(5),
+        ];
+        let calls = vec![ToolCallEvent {
+            at: at("2025-06-01T12:00:00Z"),
+            session_id: "s1".to_string(),
+            project: "/work/app".to_string(),
+            name: "Bash".to_string(),
+            subject: None,
+            is_error: Some(false),
+            latency_ms: Some(10),
+        }];
+
+        let filled = fill_missing(&mut stats, derive(&events, &calls));
+        assert!(filled.contains(&"dailyActivity".to_string()));
+        assert!(!filled.contains(&"totalMessages".to_string()));
+        assert_eq!(stats.total_messages, Some(99));
+        assert_eq!(stats.total_sessions, Some(2));
+
+        let day = &stats.daily_activity[0];
+        assert_eq!(
+            (day.message_count, day.session_count, day.tool_call_count),
+            (3, 2, 1)
+        );
+        assert_eq!(stats.model_usage["claude-sonnet-4-5"].input_tokens, 30);
+        assert_eq!(stats.hour_counts.values().sum::<u64>(), 2);
+    }
+}
@@ -0,0 +1,282 @@
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::analytics_db;
+use super::usage::{self, DayRange, ToolCallEvent};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStats {
+    pub name: String,
+    // MCP server for `mcp__<server>__<tool>` names.
+    pub server: Option<String>,
+    pub calls: u64,
+    pub sessions: u64,
+    pub completed: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub avg_latency_ms: Option<f64>,
+    pub p50_latency_ms: Option<i64>,
+    pub p95_latency_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolBreakdown {
+    pub key: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub tools: Vec<ToolStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReport {
+    pub calls: u64,
+    pub errors: u64,
+    pub tools: Vec<ToolStats>,
+    pub by_project: Vec<ToolBreakdown>,
+    pub by_day: Vec<ToolBreakdown>,
+}
+
+#[derive(Default)]
+struct Acc<'a> {
+    calls: u64,
+    sessions: HashSet<&'a str>,
+    completed: u64,
+    errors: u64,
+    latencies: Vec<i64>,
+}
+
+impl<'a> Acc<'a> {
+    fn add(&mut self, call: &'a ToolCallEvent) {
+        self.calls += 1;
+        self.sessions.insert(&call.session_id);
+        if let Some(is_error) = call.is_error {
+            self.completed += 1;
+            if is_error {
+                self.errors += 1;
+            }
+        }
+        if let Some(latency) = call.latency_ms {
+            self.latencies.push(latency);
+        }
+    }
+
+    fn finish(mut self, name: String) -> ToolStats {
+        self.latencies.sort_unstable();
+        let percentile = |p: usize| -> Option<i64> {
+            if self.latencies.is_empty() {
+                return None;
+            }
+            let idx = (self.latencies.len() * p).div_ceil(100).max(1) - 1;
+            Some(self.latencies[idx])
+        };
+
+        ToolStats {
+            server: mcp_server(&name),
+            name,
+            calls: self.calls,
+            sessions: self.sessions.len() as u64,
+            completed: self.completed,
+            errors: self.errors,
+            // Only calls that have a result can have failed.
+            error_rate: if self.completed > 0 {
+                self.errors as f64 / self.completed as f64
+            } else {
+                0.0
+            },
+            avg_latency_ms: if self.latencies.is_empty() {
+                None
+            } else {
+                Some(self.latencies.iter().sum::<i64>() as f64 / self.latencies.len() as f64)
+            },
+            p50_latency_ms: percentile(50),
+            p95_latency_ms: percentile(95),
+        }
+    }
+}
+
+fn mcp_server(name: &str) -> Option<String> {
+    let rest = name.strip_prefix("mcp__")?;
+    rest.split_once("__").map(|(server, _)| server.to_string())
+}
+
+fn tool_stats<'a>(calls: &[&'a ToolCallEvent]) -> Vec<ToolStats> {
+    let mut by_name: HashMap<&'a str, Acc<'a>> = HashMap::new();
+    for call in calls {
+        by_name.entry(call.name.as_str()).or_default().add(call);
+    }
+
+    let mut tools: Vec<ToolStats> = by_name
+        .into_iter()
+        .map(|(name, acc)| acc.finish(name.to_string()))
+        .collect();
+    tools.sort_by_key(|t| (Reverse(t.calls), t.name.clone()));
+    tools
+}
+
+fn breakdown(groups: BTreeMap<String, Vec<&ToolCallEvent>>) -> Vec<ToolBreakdown> {
+    groups
+        .into_iter()
+        .map(|(key, calls)| {
+            let tools = tool_stats(&calls);
+            ToolBreakdown {
+                key,
+                calls: tools.iter().map(|t| t.calls).sum(),
+                errors: tools.iter().map(|t| t.errors).sum(),
+                tools,
+            }
+        })
+        .collect()
+}
+
+pub fn build_report(calls: &[ToolCallEvent], project: Option<&str>, range: DayRange) -> ToolReport {
+    let calls: Vec<&ToolCallEvent> = calls
+        .iter()
+        .filter(|c| range.contains(c.at) && project.is_none_or(|p| c.project == p))
+        .collect();
+
+    let mut by_project: BTreeMap<String, Vec<&ToolCallEvent>> = BTreeMap::new();
+    let mut by_day: BTreeMap<String, Vec<&ToolCallEvent>> = BTreeMap::new();
+    for call in &calls {
+        by_project
+            .entry(call.project.clone())
+            .or_default()
+            .push(call);
+        by_day
+            .entry(usage::local_day(call.at))
+            .or_default()
+            .push(call);
+    }
+
+    let tools = tool_stats(&calls);
+    let mut by_project = breakdown(by_project);
+    by_project.sort_by_key(|p| Reverse(p.calls));
+
+    ToolReport {
+        calls: tools.iter().map(|t| t.calls).sum(),
+        errors: tools.iter().map(|t| t.errors).sum(),
+        tools,
+        by_project,
+        by_day: breakdown(by_day),
+    }
+}
+
+#[tauri::command(async)]
+pub fn get_tool_analytics(
+    project: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<ToolReport, String> {
+    let range = DayRange::parse(&from, &to)?;
+    let calls = analytics_db::load_tool_calls(range, project.as_deref())?;
+    Ok(build_report(&calls, project.as_deref(), range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::usage::test_support::at;
+
+    fn call(ts: &str, project: &str, name: &str, result: Option<(bool, i64)>) -> ToolCallEvent {
+        ToolCallEvent {
+            at: at(ts),
+            session_id: format!("s-{}", &ts[..10]),
+            project: project.to_string(),
+            name: name.to_string(),
+            subject: None,
+            is_error: result.map(|(e, _)| e),
+            latency_ms: result.map(|(_, l)| l),
+        }
+    }
+
+    #[test]
+    fn test_report_counts_errors_and_latency() {
+        let calls = vec![
+            call(
+                "2025-06-01T12:00:00Z",
+                "/work/app",
+                "Bash",
+                Some((false, 1000)),
+            ),
+            call(
+                "2025-06-01T12:01:00Z",
+                "/work/app",
+                "Bash",
+                Some((true, 3000)),
+            ),
+            call(
+                "2025-06-01T12:02:00Z",
+                "/work/app",
+                "Bash",
+                Some((false, 2000)),
+            ),
+            call("2025-06-01T12:03:00Z", "/work/app", "Bash", None),
+            call(
+                "2025-06-02T12:00:00Z",
+                "/work/lib",
+                "Read",
+                Some((false, 50)),
+            ),
+            call(
+                "2025-06-02T12:01:00Z",
+                "/work/lib",
+                "mcp__github__create_issue",
+                Some((true, 800)),
+            ),
+        ];
+
+        let report = build_report(&calls, None, DayRange::default());
+        assert_eq!(report.calls, 6);
+        assert_eq!(report.errors, 2);
+
+        let bash = &report.tools[0];
+        assert_eq!(bash.name, "Bash");
+        assert_eq!((bash.calls, bash.completed, bash.errors), (4, 3, 1));
+        assert!((bash.error_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(bash.avg_latency_ms, Some(2000.0));
+        assert_eq!(bash.p50_latency_ms, Some(2000));
+        assert_eq!(bash.p95_latency_ms, Some(3000));
+
+        let mcp = report
+            .tools
+            .iter()
+            .find(|t| t.name.starts_with("mcp__"))
+            .unwrap();
+        assert_eq!(mcp.server.as_deref(), Some("github"));
+        assert_eq!(bash.sessions, 1);
+
+        assert_eq!(report.by_project[0].key, "/work/app");
+        assert_eq!(report.by_project[1].errors, 1);
+        assert_eq!(report.by_day.len(), 2);
+        assert_eq!(report.by_day[1].calls, 2);
+    }
+
+    #[test]
+    fn test_report_filters_project_and_range() {
+        let calls = vec![
+            call(
+                "2025-06-01T12:00:00Z",
+                "/work/app",
+                "Edit",
+                Some((false, 10)),
+            ),
+            call(
+                "2025-06-03T12:00:00Z",
+                "/work/app",
+                "Edit",
+                Some((false, 10)),
+            ),
+            call(
+                "2025-06-03T12:00:00Z",
+                "/work/lib",
+                "Edit",
+                Some((false, 10)),
+            ),
+        ];
+        let range = DayRange::parse(&Some("2025-06-02".to_string()), &None).unwrap();
+
+        let report = build_report(&calls, Some("/work/app"), range);
+        assert_eq!(report.calls, 1);
+        assert_eq!(report.by_project.len(), 1);
+    }
+}
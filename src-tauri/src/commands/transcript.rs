@@ -0,0 +1,314 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_creation_1h_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn from_message(message: &Value) -> Option<TokenUsage> {
+        let usage = message.get("usage")?;
+        let field = |name: &str| usage.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+
+        // `cache_creation` splits the write total by TTL; older transcripts
+        // only have the total, which was always the 5 minute TTL.
+        let cache_creation_1h_input_tokens = usage
+            .get("cache_creation")
+            .and_then(|c| c.get("ephemeral_1h_input_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        Some(TokenUsage {
+            input_tokens: field("input_tokens"),
+            output_tokens: field("output_tokens"),
+            cache_creation_input_tokens: field("cache_creation_input_tokens"),
+            cache_creation_1h_input_tokens,
+            cache_read_input_tokens: field("cache_read_input_tokens"),
+        })
+    }
+
+    pub fn cache_creation_5m_input_tokens(&self) -> u64 {
+        self.cache_creation_input_tokens
+            .saturating_sub(self.cache_creation_1h_input_tokens)
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_creation_1h_input_tokens += other.cache_creation_1h_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    // Field-wise growth from `earlier` to `self`, never negative.
+    pub fn saturating_sub(&self, earlier: &TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens.saturating_sub(earlier.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(earlier.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .saturating_sub(earlier.cache_creation_input_tokens),
+            cache_creation_1h_input_tokens: self
+                .cache_creation_1h_input_tokens
+                .saturating_sub(earlier.cache_creation_1h_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .saturating_sub(earlier.cache_read_input_tokens),
+        }
+    }
+
+    fn max(&self, other: &TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens.max(other.input_tokens),
+            output_tokens: self.output_tokens.max(other.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .max(other.cache_creation_input_tokens),
+            cache_creation_1h_input_tokens: self
+                .cache_creation_1h_input_tokens
+                .max(other.cache_creation_1h_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .max(other.cache_read_input_tokens),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+// Claude Code writes one line per content block of a streamed response, and
+// every one of them repeats that response's usage. Lines sharing a message id
+// and request id belong to the same API call.
+pub fn message_key(entry: &Value) -> Option<String> {
+    let message_id = entry
+        .get("message")
+        .and_then(|m| m.get("id"))
+        .and_then(|i| i.as_str());
+    let request_id = entry.get("requestId").and_then(|r| r.as_str());
+
+    match (message_id, request_id) {
+        (None, None) => None,
+        (m, r) => Some(format!("{}:{}", m.unwrap_or(""), r.unwrap_or(""))),
+    }
+}
+
+pub struct UsageDelta {
+    pub usage: TokenUsage,
+    pub first: bool,
+}
+
+// Counts each API call once. Repeated records only contribute whatever they
+// report beyond the largest usage already seen for the call, so the running
+// total equals the API-level count however the response was split.
+#[derive(Debug, Default)]
+pub struct UsageDedup {
+    seen: HashMap<String, TokenUsage>,
+}
+
+impl UsageDedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: &Value, usage: TokenUsage) -> UsageDelta {
+        let key = match message_key(entry) {
+            Some(k) => k,
+            None => return UsageDelta { usage, first: true },
+        };
+
+        match self.seen.get_mut(&key) {
+            Some(previous) => {
+                let delta = usage.saturating_sub(previous);
+                *previous = previous.max(&usage);
+                UsageDelta {
+                    usage: delta,
+                    first: false,
+                }
+            }
+            None => {
+                self.seen.insert(key, usage);
+                UsageDelta { usage, first: true }
+            }
+        }
+    }
+}
+
+pub struct NewLines {
+    pub lines: Vec<Value>,
+    pub offset: u64,
+    pub reset: bool,
+}
+
+// Reads the complete lines appended since `offset`. A trailing line without a
+// newline is still being written and is left for the next call. A file that
+// shrank was rewritten, so it is read again from the start with `reset` set.
+pub fn read_new_lines(path: &Path, offset: u64) -> Result<NewLines, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let reset = len < offset;
+    let start = if reset { 0 } else { offset };
+
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| e.to_string())?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+
+    let complete = match buf.iter().rposition(|b| *b == b'\n') {
+        Some(idx) => idx + 1,
+        None => 0,
+    };
+
+    let lines = buf[..complete]
+        .split(|b| *b == b'\n')
+        .filter_map(|line| serde_json::from_slice::<Value>(line).ok())
+        .collect();
+
+    Ok(NewLines {
+        lines,
+        offset: start + complete as u64,
+        reset,
+    })
+}
+
+#[derive(Default)]
+pub struct TranscriptTail {
+    offsets: HashMap<PathBuf, u64>,
+}
+
+impl TranscriptTail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read(&mut self, path: &Path) -> Option<NewLines> {
+        let offset = self.offsets.get(path).copied().unwrap_or(0);
+        let new_lines = read_new_lines(path, offset).ok()?;
+        self.offsets.insert(path.to_path_buf(), new_lines.offset);
+        Some(new_lines)
+    }
+
+    pub fn forget(&mut self, path: &Path) {
+        self.offsets.remove(path);
+    }
+}
+
+pub fn parse_timestamp(entry: &Value) -> Option<DateTime<Utc>> {
+    let ts = entry.get("timestamp")?.as_str()?;
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+pub fn entry_type(entry: &Value) -> &str {
+    entry.get("type").and_then(|t| t.as_str()).unwrap_or("")
+}
+
+pub fn content_blocks(entry: &Value) -> &[Value] {
+    entry
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+pub fn block_type(block: &Value) -> &str {
+    block.get("type").and_then(|t| t.as_str()).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_token_usage_from_message() {
+        let message = json!({
+            "usage": {
+                "input_tokens": 10,
+                "output_tokens": 20,
+                "cache_creation_input_tokens": 30,
+                "cache_read_input_tokens": 40,
+                "cache_creation": {
+                    "ephemeral_5m_input_tokens": 18,
+                    "ephemeral_1h_input_tokens": 12
+                }
+            }
+        });
+        let usage = TokenUsage::from_message(&message).unwrap();
+        assert_eq!(usage.total(), 100);
+        assert_eq!(usage.cache_creation_1h_input_tokens, 12);
+        assert_eq!(usage.cache_creation_5m_input_tokens(), 18);
+        assert!(TokenUsage::from_message(&json!({})).is_none());
+    }
+
+    #[test]
+    fn test_usage_dedup_counts_streamed_records_once() {
+        let fixture = include_str!("../../tests/fixtures/streamed-response.jsonl");
+        let mut dedup = UsageDedup::new();
+        let mut total = TokenUsage::default();
+        let mut calls = 0;
+
+        for line in fixture.lines() {
+            let entry: Value = serde_json::from_str(line).unwrap();
+            if entry_type(&entry) != "assistant" {
+                continue;
+            }
+            let usage = TokenUsage::from_message(&entry["message"]).unwrap();
+            let delta = dedup.record(&entry, usage);
+            total.add(&delta.usage);
+            if delta.first {
+                calls += 1;
+            }
+        }
+
+        // Two API calls: msg_01 streamed over three lines, msg_02 over two.
+        assert_eq!(calls, 2);
+        assert_eq!(
+            total,
+            TokenUsage {
+                input_tokens: 3 + 5,
+                output_tokens: 412 + 96,
+                cache_creation_input_tokens: 2048,
+                cache_creation_1h_input_tokens: 0,
+                cache_read_input_tokens: 18000 + 20048,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_new_lines_skips_partial_line() {
+        let path = std::env::temp_dir().join(format!("cockpit-tail-{}.jsonl", std::process::id()));
+        fs::write(&path, "{\"a\":1}\n{\"b\":2}\n{\"c\":").unwrap();
+
+        let first = read_new_lines(&path, 0).unwrap();
+        assert_eq!(first.lines.len(), 2);
+        assert!(!first.reset);
+
+        fs::write(&path, "{\"a\":1}\n{\"b\":2}\n{\"c\":3}\n").unwrap();
+        let second = read_new_lines(&path, first.offset).unwrap();
+        assert_eq!(second.lines, vec![json!({"c": 3})]);
+
+        fs::write(&path, "{\"d\":4}\n").unwrap();
+        let third = read_new_lines(&path, second.offset).unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(third.reset);
+        assert_eq!(third.lines, vec![json!({"d": 4})]);
+    }
+}
@@ -1,22 +1,1015 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
 use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
+use super::analytics_db;
+use super::pricing::{self, CostEstimate, Pricing};
+use super::transcript::{self, NewLines, TokenUsage, TranscriptTail, UsageDedup};
 use super::utils;
 
-#[tauri::command]
-pub fn read_stats_cache() -> Result<Value, String> {
-    let base = utils::claude_dir();
-    let candidates = ["stats-cache.json", "statsig-cache.json"];
+static ENGINE: OnceLock<Mutex<UsageEngine>> = OnceLock::new();
 
-    for name in &candidates {
-        let path = base.join(name);
-        if path.exists() {
-            let content = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
-            return serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse {}: {}", name, e));
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageRow {
+    pub key: String,
+    pub messages: u64,
+    pub tokens: TokenUsage,
+    pub total_tokens: u64,
+    pub cost: CostEstimate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub project: String,
+    pub file_path: String,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+    pub messages: u64,
+    pub tokens: TokenUsage,
+    pub total_tokens: u64,
+    pub cost: CostEstimate,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    pub messages: u64,
+    pub tokens: TokenUsage,
+    pub total_tokens: u64,
+    pub cost: CostEstimate,
+    pub by_day: Vec<UsageRow>,
+    pub by_model: Vec<UsageRow>,
+    pub by_project: Vec<UsageRow>,
+    pub by_session: Vec<SessionUsage>,
+}
+
+// Inclusive range of local days, either end optional.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DayRange {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl DayRange {
+    pub fn parse(from: &Option<String>, to: &Option<String>) -> Result<DayRange, String> {
+        let range = DayRange {
+            from: parse_day(from)?,
+            to: parse_day(to)?,
+        };
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from > to {
+                return Err("Start date is after end date".to_string());
+            }
+        }
+        Ok(range)
+    }
+
+    // Both ends as `YYYY-MM-DD`, for comparing against stored local days.
+    pub fn bounds(&self) -> (Option<String>, Option<String>) {
+        let day = |d: Option<NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+        (day(self.from), day(self.to))
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let day = at.with_timezone(&Local).date_naive();
+        self.from.is_none_or(|f| day >= f) && self.to.is_none_or(|t| day <= t)
+    }
+}
+
+fn parse_day(value: &Option<String>) -> Result<Option<NaiveDate>, String> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("Invalid date `{}`, expected YYYY-MM-DD", s)),
+    }
+}
+
+pub fn local_day(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%Y-%m-%d").to_string()
+}
+
+#[derive(Debug, Clone)]
+pub struct UsageEvent {
+    pub at: DateTime<Utc>,
+    pub session_id: String,
+    pub project: String,
+    pub model: String,
+    pub tokens: TokenUsage,
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolCallEvent {
+    pub at: DateTime<Utc>,
+    pub session_id: String,
+    pub project: String,
+    pub name: String,
+    // What permission rules match against: the command, path or URL.
+    pub subject: Option<String>,
+    // Both unknown until the tool's result has been written.
+    pub is_error: Option<bool>,
+    pub latency_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileEditEvent {
+    pub at: DateTime<Utc>,
+    pub session_id: String,
+    pub project: String,
+    pub file_path: String,
+    pub lines_added: u64,
+    pub lines_removed: u64,
+}
+
+#[derive(Debug, Clone)]
+struct ToolCall {
+    at: DateTime<Utc>,
+    name: String,
+    subject: Option<String>,
+    is_error: Option<bool>,
+    latency_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+struct FileEdit {
+    // Index into `tool_calls`, so edits that failed can be left out.
+    call: usize,
+    file_path: String,
+    lines_added: u64,
+    lines_removed: u64,
+}
+
+fn tool_subject(name: &str, input: &Value) -> Option<String> {
+    let key = match name {
+        "Bash" => "command",
+        "Read" | "Edit" | "MultiEdit" | "Write" => "file_path",
+        "NotebookRead" | "NotebookEdit" => "notebook_path",
+        "Glob" | "Grep" | "LS" => "path",
+        "WebFetch" => "url",
+        _ => return None,
+    };
+    input.get(key).and_then(|v| v.as_str()).map(str::to_string)
+}
+
+// Lines added and removed by replacing `old` with `new`. Edits carry unchanged
+// context around the change, so matching lines at either end are not counted.
+fn changed_lines(old: &str, new: &str) -> (u64, u64) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (
+        (new.len() - prefix - suffix) as u64,
+        (old.len() - prefix - suffix) as u64,
+    )
+}
+
+impl FileEdit {
+    fn from_tool_use(call: usize, name: &str, input: &Value) -> Option<FileEdit> {
+        let file_path = input.get("file_path").and_then(|p| p.as_str())?;
+        fn text<'a>(value: &'a Value, key: &str) -> &'a str {
+            value.get(key).and_then(|s| s.as_str()).unwrap_or("")
         }
+        let replace =
+            |edit: &Value| changed_lines(text(edit, "old_string"), text(edit, "new_string"));
+
+        let (lines_added, lines_removed) = match name {
+            "Edit" => replace(input),
+            "MultiEdit" => input
+                .get("edits")
+                .and_then(|e| e.as_array())
+                .map(|edits| {
+                    edits
+                        .iter()
+                        .map(replace)
+                        .fold((0, 0), |(added, removed), (a, r)| (added + a, removed + r))
+                })
+                .unwrap_or((0, 0)),
+            // Overwrites are counted as added lines only; the previous content
+            // is not in the transcript.
+            "Write" => (text(input, "content").lines().count() as u64, 0),
+            _ => return None,
+        };
+
+        Some(FileEdit {
+            call,
+            file_path: file_path.to_string(),
+            lines_added,
+            lines_removed,
+        })
     }
+}
 
-    Ok(Value::Null)
+#[derive(Debug, Clone)]
+struct MessageUsage {
+    at: DateTime<Utc>,
+    model: String,
+    tokens: TokenUsage,
+}
+
+#[derive(Debug, Default)]
+struct Tally {
+    messages: u64,
+    tokens: TokenUsage,
+    cost: CostEstimate,
+}
+
+impl Tally {
+    fn add(&mut self, other: &Tally) {
+        self.messages += other.messages;
+        self.tokens.add(&other.tokens);
+        self.cost.merge(&other.cost);
+    }
+
+    fn priced(&self, model: &str, pricing: &Pricing) -> Tally {
+        let mut cost = CostEstimate::default();
+        cost.add(model, &self.tokens, pricing);
+        Tally {
+            messages: self.messages,
+            tokens: self.tokens,
+            cost,
+        }
+    }
+}
+
+// Per transcript, so a rewritten file can simply be dropped and re-read. Reports
+// are built from the analytics database, which outlives the transcripts.
+#[derive(Debug, Default)]
+struct FileUsage {
+    project: Option<String>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    messages: Vec<MessageUsage>,
+    message_index: HashMap<String, usize>,
+    dedup: UsageDedup,
+    tool_calls: Vec<ToolCall>,
+    pending_tools: HashMap<String, usize>,
+    edits: Vec<FileEdit>,
+    // Rows before these indexes are unchanged since the last database sync.
+    synced_messages: usize,
+    synced_tool_calls: usize,
+}
+
+impl FileUsage {
+    fn apply(&mut self, entry: &Value) {
+        if self.project.is_none() {
+            if let Some(cwd) = entry.get("cwd").and_then(|c| c.as_str()) {
+                self.project = Some(cwd.to_string());
+            }
+        }
+
+        match transcript::entry_type(entry) {
+            "assistant" => {
+                self.apply_tool_uses(entry);
+                self.apply_usage(entry);
+            }
+            "user" => self.apply_tool_results(entry),
+            _ => {}
+        }
+    }
+
+    fn apply_tool_uses(&mut self, entry: &Value) {
+        let at = match transcript::parse_timestamp(entry) {
+            Some(ts) => ts,
+            None => return,
+        };
+
+        for block in transcript::content_blocks(entry) {
+            if transcript::block_type(block) != "tool_use" {
+                continue;
+            }
+            let id = block.get("id").and_then(|i| i.as_str()).unwrap_or("");
+            if self.pending_tools.contains_key(id) {
+                continue;
+            }
+            if !id.is_empty() {
+                self.pending_tools
+                    .insert(id.to_string(), self.tool_calls.len());
+            }
+            let name = block
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("unknown");
+            let input = block.get("input").unwrap_or(&Value::Null);
+            if let Some(edit) = FileEdit::from_tool_use(self.tool_calls.len(), name, input) {
+                self.edits.push(edit);
+            }
+            self.tool_calls.push(ToolCall {
+                at,
+                name: name.to_string(),
+                subject: tool_subject(name, input),
+                is_error: None,
+                latency_ms: None,
+            });
+        }
+    }
+
+    fn apply_tool_results(&mut self, entry: &Value) {
+        let at = transcript::parse_timestamp(entry);
+
+        for block in transcript::content_blocks(entry) {
+            if transcript::block_type(block) != "tool_result" {
+                continue;
+            }
+            let idx = match block
+                .get("tool_use_id")
+                .and_then(|i| i.as_str())
+                .and_then(|id| self.pending_tools.remove(id))
+            {
+                Some(idx) => idx,
+                None => continue,
+            };
+
+            self.synced_tool_calls = self.synced_tool_calls.min(idx);
+            let call = &mut self.tool_calls[idx];
+            call.is_error = Some(
+                block
+                    .get("is_error")
+                    .and_then(|e| e.as_bool())
+                    .unwrap_or(false),
+            );
+            call.latency_ms = at.map(|at| (at - call.at).num_milliseconds().max(0));
+        }
+    }
+
+    fn apply_usage(&mut self, entry: &Value) {
+        let message = match entry.get("message") {
+            Some(m) => m,
+            None => return,
+        };
+        let usage = match TokenUsage::from_message(message) {
+            Some(u) => u,
+            None => return,
+        };
+        let timestamp = match transcript::parse_timestamp(entry) {
+            Some(ts) => ts,
+            None => return,
+        };
+
+        self.first_timestamp.get_or_insert(timestamp);
+        self.last_timestamp = Some(timestamp);
+
+        let model = message
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let delta = self.dedup.record(entry, usage);
+
+        let existing =
+            transcript::message_key(entry).and_then(|key| self.message_index.get(&key).copied());
+        match existing {
+            Some(idx) if !delta.first => {
+                self.synced_messages = self.synced_messages.min(idx);
+                self.messages[idx].tokens.add(&delta.usage);
+            }
+            _ => {
+                if let Some(key) = transcript::message_key(entry) {
+                    self.message_index.insert(key, self.messages.len());
+                }
+                self.messages.push(MessageUsage {
+                    at: timestamp,
+                    model,
+                    tokens: delta.usage,
+                });
+            }
+        }
+    }
+}
+
+// Messages, tool calls and file edits of one transcript that are new or
+// changed since the last sync, with the row index of the first of each. Edits
+// are keyed by the row of the tool call that made them.
+#[derive(Debug, Clone)]
+pub struct SessionChanges {
+    pub file_path: PathBuf,
+    pub session_id: String,
+    pub project: String,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    pub message_count: usize,
+    pub messages_from: usize,
+    pub messages: Vec<UsageEvent>,
+    pub tool_call_count: usize,
+    pub tool_calls_from: usize,
+    pub tool_calls: Vec<ToolCallEvent>,
+    pub edits: Vec<(usize, FileEditEvent)>,
+}
+
+#[derive(Default)]
+pub struct UsageEngine {
+    tail: TranscriptTail,
+    files: HashMap<PathBuf, FileUsage>,
+}
+
+pub fn engine() -> &'static Mutex<UsageEngine> {
+    ENGINE.get_or_init(|| Mutex::new(UsageEngine::default()))
+}
+
+impl UsageEngine {
+    pub fn update_file(&mut self, path: &Path) {
+        if !path.exists() {
+            self.tail.forget(path);
+            self.files.remove(path);
+            return;
+        }
+        if let Some(new_lines) = self.tail.read(path) {
+            self.ingest(path, &new_lines);
+        }
+    }
+
+    fn ingest(&mut self, path: &Path, new_lines: &NewLines) {
+        let file = self.files.entry(path.to_path_buf()).or_default();
+        if new_lines.reset {
+            *file = FileUsage::default();
+        }
+        for entry in &new_lines.lines {
+            file.apply(entry);
+        }
+    }
+
+    // Brings every transcript under `projects_dir` up to date, reading only
+    // what was appended since the last scan.
+    pub fn refresh(&mut self, projects_dir: &Path) {
+        let mut seen = HashSet::new();
+
+        if let Ok(dirs) = fs::read_dir(projects_dir) {
+            for dir in dirs.filter_map(|d| d.ok()) {
+                if !dir.path().is_dir() {
+                    continue;
+                }
+                let files = match utils::list_jsonl_files(&dir.path()) {
+                    Ok(f) => f,
+                    Err(_) => continue,
+                };
+                for file in files {
+                    let path = file.path();
+                    self.update_file(&path);
+                    seen.insert(path);
+                }
+            }
+        }
+
+        let stale: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|p| !seen.contains(*p))
+            .cloned()
+            .collect();
+        for path in stale {
+            self.tail.forget(&path);
+            self.files.remove(&path);
+        }
+    }
+
+    pub fn pending_changes(&self) -> Vec<SessionChanges> {
+        let mut changes = Vec::new();
+        for (path, file) in &self.files {
+            if file.synced_messages == file.messages.len()
+                && file.synced_tool_calls == file.tool_calls.len()
+            {
+                continue;
+            }
+            let project = project_key(path, file);
+            let session_id = session_id(path);
+            changes.push(SessionChanges {
+                file_path: path.clone(),
+                session_id: session_id.clone(),
+                project: project.clone(),
+                first_timestamp: file.first_timestamp,
+                last_timestamp: file.last_timestamp,
+                message_count: file.messages.len(),
+                messages_from: file.synced_messages,
+                messages: file.messages[file.synced_messages..]
+                    .iter()
+                    .map(|m| UsageEvent {
+                        at: m.at,
+                        session_id: session_id.clone(),
+                        project: project.clone(),
+                        model: m.model.clone(),
+                        tokens: m.tokens,
+                    })
+                    .collect(),
+                tool_call_count: file.tool_calls.len(),
+                tool_calls_from: file.synced_tool_calls,
+                tool_calls: file.tool_calls[file.synced_tool_calls..]
+                    .iter()
+                    .map(|c| ToolCallEvent {
+                        at: c.at,
+                        session_id: session_id.clone(),
+                        project: project.clone(),
+                        name: c.name.clone(),
+                        subject: c.subject.clone(),
+                        is_error: c.is_error,
+                        latency_ms: c.latency_ms,
+                    })
+                    .collect(),
+                edits: file
+                    .edits
+                    .iter()
+                    .filter(|e| e.call >= file.synced_tool_calls)
+                    .map(|e| {
+                        let event = FileEditEvent {
+                            at: file.tool_calls[e.call].at,
+                            session_id: session_id.clone(),
+                            project: project.clone(),
+                            file_path: e.file_path.clone(),
+                            lines_added: e.lines_added,
+                            lines_removed: e.lines_removed,
+                        };
+                        (e.call, event)
+                    })
+                    .collect(),
+            });
+        }
+        changes
+    }
+
+    pub fn mark_synced(&mut self, changes: &[SessionChanges]) {
+        for change in changes {
+            if let Some(file) = self.files.get_mut(&change.file_path) {
+                file.synced_messages = change.message_count;
+                file.synced_tool_calls = change.tool_call_count;
+            }
+        }
+    }
+}
+
+fn session_id(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+fn project_key(path: &Path, file: &FileUsage) -> String {
+    file.project.clone().unwrap_or_else(|| {
+        path.parent()
+            .and_then(|p| p.file_name())
+            .map(|n| utils::decode_encoded_path(&n.to_string_lossy()))
+            .unwrap_or_default()
+    })
+}
+
+fn into_rows(tallies: HashMap<String, Tally>) -> Vec<UsageRow> {
+    tallies
+        .into_iter()
+        .map(|(key, tally)| UsageRow {
+            key,
+            messages: tally.messages,
+            tokens: tally.tokens,
+            total_tokens: tally.tokens.total(),
+            cost: tally.cost,
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct SessionTally {
+    first: Option<DateTime<Utc>>,
+    last: Option<DateTime<Utc>>,
+    buckets: HashMap<(String, String), Tally>,
+}
+
+// `files` maps (project, session id) to the session's transcript, which may
+// since have been deleted.
+pub fn build_report(
+    events: &[UsageEvent],
+    files: &HashMap<(String, String), String>,
+    pricing: &Pricing,
+) -> UsageReport {
+    let mut sessions: HashMap<(&str, &str), SessionTally> = HashMap::new();
+    for event in events {
+        let session = sessions
+            .entry((&event.project, &event.session_id))
+            .or_default();
+        session.first = Some(session.first.map_or(event.at, |t| t.min(event.at)));
+        session.last = session.last.max(Some(event.at));
+        let tally = session
+            .buckets
+            .entry((local_day(event.at), event.model.clone()))
+            .or_default();
+        tally.messages += 1;
+        tally.tokens.add(&event.tokens);
+    }
+
+    let mut total = Tally::default();
+    let mut by_day: HashMap<String, Tally> = HashMap::new();
+    let mut by_model: HashMap<String, Tally> = HashMap::new();
+    let mut by_project: HashMap<String, Tally> = HashMap::new();
+    let mut by_session = Vec::new();
+
+    for ((project, session_id), session) in sessions {
+        let mut session_total = Tally::default();
+        for ((day, model), tally) in &session.buckets {
+            let tally = tally.priced(model, pricing);
+            by_day.entry(day.clone()).or_default().add(&tally);
+            by_model.entry(model.clone()).or_default().add(&tally);
+            by_project
+                .entry(project.to_string())
+                .or_default()
+                .add(&tally);
+            session_total.add(&tally);
+            total.add(&tally);
+        }
+
+        by_session.push(SessionUsage {
+            session_id: session_id.to_string(),
+            project: project.to_string(),
+            file_path: files
+                .get(&(project.to_string(), session_id.to_string()))
+                .cloned()
+                .unwrap_or_default(),
+            first_timestamp: session.first.map(|t| t.to_rfc3339()),
+            last_timestamp: session.last.map(|t| t.to_rfc3339()),
+            messages: session_total.messages,
+            tokens: session_total.tokens,
+            total_tokens: session_total.tokens.total(),
+            cost: session_total.cost,
+        });
+    }
+
+    let mut by_day = into_rows(by_day);
+    by_day.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut by_model = into_rows(by_model);
+    by_model.sort_by_key(|r| Reverse(r.total_tokens));
+
+    let mut by_project = into_rows(by_project);
+    by_project.sort_by_key(|r| Reverse(r.total_tokens));
+
+    by_session.sort_by(|a, b| b.last_timestamp.cmp(&a.last_timestamp));
+
+    UsageReport {
+        messages: total.messages,
+        tokens: total.tokens,
+        total_tokens: total.tokens.total(),
+        cost: total.cost,
+        by_day,
+        by_model,
+        by_project,
+        by_session,
+    }
+}
+
+#[tauri::command(async)]
+pub fn get_usage_report() -> Result<UsageReport, String> {
+    analytics_db::load_report(&pricing::current())
+}
+
+// Usage event fixtures shared by the tests of every module built on them.
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+
+    pub fn at(ts: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(ts)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    // A token-less sonnet event in session `s1` of `/work/app`; chain the
+    // setters below for anything else.
+    pub fn event(ts: &str) -> UsageEvent {
+        UsageEvent {
+            at: at(ts),
+            session_id: "s1".to_string(),
+            project: "/work/app".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            tokens: TokenUsage::default(),
+        }
+    }
+
+    impl UsageEvent {
+        pub fn session(mut self, session_id: &str) -> Self {
+            self.session_id = session_id.to_string();
+            self
+        }
+
+        pub fn project(mut self, project: &str) -> Self {
+            self.project = project.to_string();
+            self
+        }
+
+        pub fn model(mut self, model: &str) -> Self {
+            self.model = model.to_string();
+            self
+        }
+
+        pub fn input(mut self, tokens: u64) -> Self {
+            self.tokens.input_tokens = tokens;
+            self
+        }
+
+        pub fn output(mut self, tokens: u64) -> Self {
+            self.tokens.output_tokens = tokens;
+            self
+        }
+
+        pub fn cache(mut self, write: u64, read: u64) -> Self {
+            self.tokens.cache_creation_input_tokens = write;
+            self.tokens.cache_read_input_tokens = read;
+            self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn projects_fixture(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cockpit-usage-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("-work-app")).unwrap();
+        fs::create_dir_all(dir.join("-work-lib")).unwrap();
+        dir
+    }
+
+    fn append(path: &Path, entries: &[Value]) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        for entry in entries {
+            writeln!(file, "{}", entry).unwrap();
+        }
+    }
+
+    fn report(engine: &UsageEngine) -> UsageReport {
+        let events: Vec<UsageEvent> = engine
+            .pending_changes()
+            .into_iter()
+            .flat_map(|c| c.messages)
+            .collect();
+        build_report(&events, &HashMap::new(), &Pricing::default())
+    }
+
+    fn assistant(ts: &str, model: &str, input: u64, output: u64, cache_read: u64) -> Value {
+        json!({
+            "type": "assistant",
+            "timestamp": ts,
+            "message": {
+                "model": model,
+                "usage": {
+                    "input_tokens": input,
+                    "output_tokens": output,
+                    "cache_read_input_tokens": cache_read
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_report_groups_by_day_model_project_session() {
+        let dir = projects_fixture("groups");
+        let app = dir.join("-work-app").join("s1.jsonl");
+        let lib = dir.join("-work-lib").join("s2.jsonl");
+
+        append(
+            &app,
+            &[
+                json!({"type": "user", "cwd": "/work/app", "timestamp": "2025-06-01T12:00:00Z"}),
+                assistant("2025-06-01T12:00:05Z", "claude-sonnet-4-5", 100, 10, 1000),
+                assistant("2025-06-02T12:00:05Z", "claude-opus-4-1", 200, 20, 0),
+            ],
+        );
+        append(
+            &lib,
+            &[assistant(
+                "2025-06-02T12:30:00Z",
+                "claude-sonnet-4-5",
+                50,
+                5,
+                0,
+            )],
+        );
+
+        let mut engine = UsageEngine::default();
+        engine.refresh(&dir);
+        let report = report(&engine);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(report.messages, 3);
+        assert_eq!(report.tokens.input_tokens, 350);
+        assert_eq!(report.tokens.cache_read_input_tokens, 1000);
+        assert_eq!(report.total_tokens, 1385);
+
+        let days: Vec<(&str, u64)> = report
+            .by_day
+            .iter()
+            .map(|r| (r.key.as_str(), r.total_tokens))
+            .collect();
+        assert_eq!(days, vec![("2025-06-01", 1110), ("2025-06-02", 275)]);
+
+        assert_eq!(report.by_model[0].key, "claude-sonnet-4-5");
+        assert_eq!(report.by_model[0].messages, 2);
+
+        let projects: Vec<&str> = report.by_project.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(projects, vec!["/work/app", "/work/lib"]);
+
+        assert_eq!(report.by_session.len(), 2);
+        assert_eq!(report.by_session[0].session_id, "s2");
+        assert_eq!(report.by_session[1].total_tokens, 1330);
+    }
+
+    #[test]
+    fn test_streamed_records_are_counted_once() {
+        let dir = projects_fixture("streamed");
+        let path = dir.join("-work-app").join("s1.jsonl");
+        fs::write(
+            &path,
+            include_str!("../../tests/fixtures/streamed-response.jsonl"),
+        )
+        .unwrap();
+
+        let mut engine = UsageEngine::default();
+        engine.refresh(&dir);
+        let report = report(&engine);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(report.messages, 2);
+        assert_eq!(report.tokens.output_tokens, 412 + 96);
+        assert_eq!(report.tokens.cache_read_input_tokens, 18000 + 20048);
+    }
+
+    #[test]
+    fn test_tool_calls_pair_with_results() {
+        let dir = projects_fixture("tools");
+        let path = dir.join("-work-app").join("s1.jsonl");
+        append(
+            &path,
+            &[
+                json!({
+                    "type": "assistant",
+                    "cwd": "/work/app",
+                    "timestamp": "2025-06-01T12:00:00Z",
+                    "message": {"content": [
+                        {"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {"command": "npm test"}},
+                        {"type": "tool_use", "id": "tu_2", "name": "Read"}
+                    ]}
+                }),
+                json!({
+                    "type": "user",
+                    "timestamp": "2025-06-01T12:00:02.500Z",
+                    "message": {"content": [
+                        {"type": "tool_result", "tool_use_id": "tu_1", "is_error": true, "content": "exit 1"}
+                    ]}
+                }),
+            ],
+        );
+
+        let mut engine = UsageEngine::default();
+        engine.refresh(&dir);
+        let mut calls: Vec<ToolCallEvent> = engine
+            .pending_changes()
+            .into_iter()
+            .flat_map(|c| c.tool_calls)
+            .collect();
+        let _ = fs::remove_dir_all(&dir);
+        calls.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].name, "Bash");
+        assert_eq!(calls[0].project, "/work/app");
+        assert_eq!(calls[0].is_error, Some(true));
+        assert_eq!(calls[0].latency_ms, Some(2500));
+        assert_eq!(calls[0].subject.as_deref(), Some("npm test"));
+        assert_eq!(calls[1].is_error, None);
+        assert_eq!(calls[1].subject, None);
+    }
+
+    #[test]
+    fn test_file_edits_from_tool_uses() {
+        let dir = projects_fixture("edits");
+        let path = dir.join("-work-app").join("s1.jsonl");
+        append(
+            &path,
+            &[
+                json!({
+                    "type": "assistant",
+                    "cwd": "/work/app",
+                    "timestamp": "2025-06-01T12:00:00Z",
+                    "message": {"content": [
+                        {"type": "tool_use", "id": "tu_1", "name": "Edit", "input": {
+                            "file_path": "/work/app/src/main.rs",
+                            "old_string": "a\nb",
+                            "new_string": "a\nb\nc"
+                        }},
+                        {"type": "tool_use", "id": "tu_2", "name": "MultiEdit", "input": {
+                            "file_path": "/work/app/src/lib.rs",
+                            "edits": [
+                                {"old_string": "x", "new_string": "y"},
+                                {"old_string": "p\nq", "new_string": ""}
+                            ]
+                        }},
+                        {"type": "tool_use", "id": "tu_3", "name": "Write", "input": {
+                            "file_path": "/work/app/README.md",
+                            "content": "one\ntwo\n"
+                        }},
+                        {"type": "tool_use", "id": "tu_4", "name": "Read", "input": {
+                            "file_path": "/work/app/src/main.rs"
+                        }}
+                    ]}
+                }),
+                json!({
+                    "type": "user",
+                    "timestamp": "2025-06-01T12:00:01Z",
+                    "message": {"content": [
+                        {"type": "tool_result", "tool_use_id": "tu_3", "is_error": true, "content": "denied"}
+                    ]}
+                }),
+            ],
+        );
+
+        let mut engine = UsageEngine::default();
+        engine.refresh(&dir);
+        let mut edits: Vec<(usize, FileEditEvent)> = engine
+            .pending_changes()
+            .into_iter()
+            .flat_map(|c| c.edits)
+            .collect();
+        let _ = fs::remove_dir_all(&dir);
+        edits.sort_by_key(|(call, _)| *call);
+
+        // Failed calls are kept here and left out when edits are read back.
+        let lines: Vec<(usize, &str, u64, u64)> = edits
+            .iter()
+            .map(|(call, e)| (*call, e.file_path.as_str(), e.lines_added, e.lines_removed))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (0, "/work/app/src/main.rs", 1, 0),
+                (1, "/work/app/src/lib.rs", 1, 3),
+                (2, "/work/app/README.md", 2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_lines_skip_unchanged_context() {
+        assert_eq!(changed_lines("a\nb", "a\nb\nc"), (1, 0));
+        assert_eq!(changed_lines("a\nb\nc", "a\nX\nY\nc"), (2, 1));
+        assert_eq!(changed_lines("a\na", "a"), (0, 1));
+        assert_eq!(changed_lines("", "x\ny"), (2, 0));
+        assert_eq!(changed_lines("same", "same"), (0, 0));
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let dir = projects_fixture("incremental");
+        let path = dir.join("-work-app").join("s1.jsonl");
+
+        append(
+            &path,
+            &[assistant(
+                "2025-06-01T12:00:00Z",
+                "claude-sonnet-4-5",
+                10,
+                1,
+                0,
+            )],
+        );
+        let mut engine = UsageEngine::default();
+        engine.refresh(&dir);
+        assert_eq!(report(&engine).total_tokens, 11);
+
+        append(
+            &path,
+            &[assistant(
+                "2025-06-01T12:01:00Z",
+                "claude-sonnet-4-5",
+                20,
+                2,
+                0,
+            )],
+        );
+        engine.update_file(&path);
+        assert_eq!(report(&engine).total_tokens, 33);
+
+        engine.refresh(&dir);
+        assert_eq!(report(&engine).messages, 2);
+
+        fs::remove_file(&path).unwrap();
+        engine.refresh(&dir);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(report(&engine).messages, 0);
+        assert!(report(&engine).by_session.is_empty());
+    }
 }
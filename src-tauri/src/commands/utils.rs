@@ -1,6 +1,6 @@
 use serde_json::Value;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub fn claude_dir() -> PathBuf {
     dirs::home_dir()
@@ -8,6 +8,12 @@ pub fn claude_dir() -> PathBuf {
         .join(".claude")
 }
 
+pub fn cockpit_dir() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join("claude-cockpit"))
+        .ok_or_else(|| "Could not find config directory".to_string())
+}
+
 pub fn projects_dir() -> PathBuf {
     claude_dir().join("projects")
 }
@@ -16,6 +22,12 @@ pub fn decode_encoded_path(encoded: &str) -> String {
     encoded.replace('-', "/")
 }
 
+pub fn encode_project_path(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
 pub fn decode_project_name(encoded: &str) -> String {
     encoded
         .replace('-', "/")
@@ -75,6 +87,11 @@ pub fn list_jsonl_files(dir: &Path) -> Result<Vec<fs::DirEntry>, String> {
     Ok(entries)
 }
 
+pub fn is_session_transcript(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("jsonl")
+        && path.parent().and_then(|p| p.parent()) == Some(projects_dir().as_path())
+}
+
 pub fn list_project_dirs() -> Result<Vec<fs::DirEntry>, String> {
     let projects_dir = projects_dir();
     if !projects_dir.exists() {
@@ -130,6 +147,24 @@ pub fn validate_scope(scope: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub fn validate_export_path(path: &str, extension: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err("Export path must be absolute".to_string());
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err("Export path contains invalid path components".to_string());
+    }
+    if path.extension().and_then(|e| e.to_str()) != Some(extension) {
+        return Err(format!("Export path must end in .{}", extension));
+    }
+    match path.parent() {
+        Some(parent) if parent.is_dir() => Ok(path),
+        Some(parent) => Err(format!("Directory does not exist: {}", parent.display())),
+        None => Err(format!("Invalid path: {}", path.display())),
+    }
+}
+
 fn canonicalize_within_projects_dir(path: &Path) -> Result<PathBuf, String> {
     let canonical = path
         .canonicalize()
@@ -229,8 +264,31 @@ mod tests {
         assert!(validate_scope("/Users/test/../etc").is_err());
     }
 
+    #[test]
+    fn test_encode_project_path() {
+        assert_eq!(
+            encode_project_path("/Users/test/project"),
+            "-Users-test-project"
+        );
+        assert_eq!(
+            encode_project_path("/home/me/my.app_v2"),
+            "-home-me-my-app-v2"
+        );
+    }
+
     #[test]
     fn test_validate_session_path_rejects_relative() {
         assert!(validate_session_path("relative/file.jsonl").is_err());
     }
+
+    #[test]
+    fn test_validate_export_path() {
+        let dir = std::env::temp_dir();
+        let ok = dir.join("usage.csv");
+        assert_eq!(validate_export_path(ok.to_str().unwrap(), "csv"), Ok(ok));
+        assert!(validate_export_path("usage.csv", "csv").is_err());
+        assert!(validate_export_path(dir.join("usage.txt").to_str().unwrap(), "csv").is_err());
+        assert!(validate_export_path(dir.join("../usage.csv").to_str().unwrap(), "csv").is_err());
+        assert!(validate_export_path("/no/such/dir/usage.csv", "csv").is_err());
+    }
 }
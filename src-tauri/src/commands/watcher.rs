@@ -1,15 +1,25 @@
+use chrono::Utc;
 use notify::RecursiveMode;
 use notify_debouncer_mini::new_debouncer;
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::OnceLock;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
 
-use super::utils;
+use super::activity;
+use super::analytics_db;
+use super::budgets;
+use super::completion::CompletionTracker;
+use super::transcript::TranscriptTail;
+use super::{usage, utils};
 
 static WATCHER_STARTED: OnceLock<()> = OnceLock::new();
 
+// Each check syncs and re-totals stored usage, so a busy session's stream of
+// transcript writes is folded into at most one check per interval.
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 fn classify_event(path: &Path) -> Option<&'static str> {
     let path_str = path.to_string_lossy();
 
@@ -33,6 +43,40 @@ fn classify_event(path: &Path) -> Option<&'static str> {
     None
 }
 
+fn check_budgets(app: &AppHandle) {
+    // Reads through the analytics database, which refreshes the engine first.
+    let check = match budgets::check() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Budget check failed: {}", e);
+            return;
+        }
+    };
+
+    for alert in &check.alerts {
+        let title = if alert.threshold >= 100 {
+            "Budget exceeded"
+        } else {
+            "Budget warning"
+        };
+        let _ = app
+            .notification()
+            .builder()
+            .title(title)
+            .body(budgets::describe(&alert.status))
+            .show();
+        let _ = app.emit("budget-alert", alert);
+    }
+
+    let warning = check
+        .statuses
+        .iter()
+        .filter(|s| s.threshold.is_some())
+        .max_by(|a, b| a.fraction.total_cmp(&b.fraction))
+        .map(budgets::describe);
+    crate::tray::set_warning(app, warning.as_deref());
+}
+
 #[tauri::command]
 pub fn start_watching(app: AppHandle) -> Result<(), String> {
     if WATCHER_STARTED.get().is_some() {
@@ -63,10 +107,15 @@ pub fn start_watching(app: AppHandle) -> Result<(), String> {
             return;
         }
 
-        let mut active_sessions: HashMap<PathBuf, Instant> = HashMap::new();
-        let session_timeout = Duration::from_secs(60);
+        let mut tail = TranscriptTail::new();
+        let mut completions = CompletionTracker::new();
+        let mut budgets_pending = false;
+        let mut last_budget_check: Option<Instant> = None;
 
         loop {
+            let mut activity_changed = false;
+            let mut usage_changed = false;
+
             match rx.recv_timeout(Duration::from_secs(10)) {
                 Ok(Ok(events)) => {
                     let mut emitted = std::collections::HashSet::new();
@@ -77,33 +126,58 @@ pub fn start_watching(app: AppHandle) -> Result<(), String> {
                             }
                         }
 
-                        if event.path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
-                            active_sessions.insert(event.path.clone(), Instant::now());
+                        if !utils::is_session_transcript(&event.path) {
+                            continue;
+                        }
+                        if let Ok(mut engine) = usage::engine().lock() {
+                            engine.update_file(&event.path);
+                            usage_changed = true;
+                        }
+                        let now = Utc::now();
+                        if let Some(completed) = completions.ingest(&event.path, now) {
+                            let _ = app_handle.emit("session-completed", completed);
+                        }
+
+                        let new_lines = match tail.read(&event.path) {
+                            Some(n) => n,
+                            None => continue,
+                        };
+                        if let Ok(mut tracker) = activity::tracker().lock() {
+                            tracker.ingest(&event.path, &new_lines.lines, now);
+                            activity_changed = true;
                         }
                     }
                 }
                 Ok(Err(e)) => {
                     eprintln!("Watch error: {}", e);
                 }
-                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => activity_changed = true,
                 Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
             }
 
-            let now = Instant::now();
-            let completed: Vec<PathBuf> = active_sessions
-                .iter()
-                .filter(|(_, last_seen)| now.duration_since(**last_seen) > session_timeout)
-                .map(|(path, _)| path.clone())
-                .collect();
-
-            for path in completed {
-                active_sessions.remove(&path);
-                let session_id = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("")
-                    .to_string();
-                let _ = app_handle.emit("session-completed", session_id);
+            if usage_changed {
+                if let Ok(mut engine) = usage::engine().lock() {
+                    if let Err(e) = analytics_db::flush(&mut engine) {
+                        eprintln!("Failed to record usage history: {}", e);
+                    }
+                }
+                budgets_pending = true;
+            }
+
+            if budgets_pending
+                && last_budget_check.is_none_or(|at| at.elapsed() >= BUDGET_CHECK_INTERVAL)
+            {
+                check_budgets(&app_handle);
+                budgets_pending = false;
+                last_budget_check = Some(Instant::now());
+            }
+
+            completions.prune(Utc::now());
+
+            if activity_changed {
+                if let Ok(mut tracker) = activity::tracker().lock() {
+                    let _ = app_handle.emit("activity-updated", tracker.snapshot(Utc::now()));
+                }
             }
         }
     });
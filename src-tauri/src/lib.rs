@@ -1,7 +1,11 @@
 mod commands;
 mod tray;
 
-use commands::{claude_md, entities, history, mcp, projects, sessions, settings, usage, watcher};
+use commands::{
+    activity, analytics_db, backups, blocks, budgets, cache, claude_md, config, entities, export,
+    history, hotspots, launcher, mcp, permission_lint, permission_rules, permission_suggestions,
+    pricing, projects, sessions, settings, stats_cache, tools, usage, watcher,
+};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -16,9 +20,15 @@ pub fn run() {
             claude_md::write_claude_md,
             settings::read_settings,
             settings::write_settings,
+            settings::validate_settings,
             settings::get_effective_settings,
             settings::add_permission,
             settings::remove_permission,
+            permission_rules::simulate_permission,
+            permission_lint::lint_permissions,
+            permission_suggestions::get_permission_suggestions,
+            backups::list_backups,
+            backups::restore_backup,
             entities::list_entities,
             entities::read_entity,
             entities::write_entity,
@@ -32,9 +42,25 @@ pub fn run() {
             history::clear_command_history,
             history::clear_all_conversations,
             watcher::start_watching,
+            activity::get_live_activity,
             sessions::list_active_sessions,
             sessions::tail_session,
-            usage::read_stats_cache,
+            sessions::interrupt_session,
+            sessions::terminate_session,
+            launcher::resume_session,
+            launcher::launch_session,
+            config::read_cockpit_config,
+            config::write_cockpit_config,
+            stats_cache::read_stats_cache,
+            usage::get_usage_report,
+            pricing::get_pricing_table,
+            budgets::get_budget_status,
+            blocks::get_usage_blocks,
+            export::export_usage,
+            tools::get_tool_analytics,
+            hotspots::get_file_hotspots,
+            cache::get_cache_report,
+            analytics_db::get_usage_history,
             mcp::list_mcp_servers,
         ])
         .setup(|app| {
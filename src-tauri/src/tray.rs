@@ -2,9 +2,12 @@ use tauri::{
     image::Image,
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager,
+    AppHandle, Manager,
 };
 
+const TRAY_ID: &str = "main";
+const TOOLTIP: &str = "Claude Cockpit";
+
 pub fn setup(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -18,8 +21,8 @@ pub fn setup(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                 .expect("failed to load tray icon")
         });
 
-    let _tray = TrayIconBuilder::new()
-        .tooltip("Claude Cockpit")
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(TOOLTIP)
         .icon(icon)
         .menu(&menu)
         .on_menu_event(|app, event| match event.id.as_ref() {
@@ -52,3 +55,21 @@ pub fn setup(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+pub fn set_warning(app: &AppHandle, warning: Option<&str>) {
+    let tray = match app.tray_by_id(TRAY_ID) {
+        Some(t) => t,
+        None => return,
+    };
+
+    match warning {
+        Some(text) => {
+            let _ = tray.set_tooltip(Some(format!("{} \u{26a0} {}", TOOLTIP, text)));
+            let _ = tray.set_title(Some("\u{26a0}"));
+        }
+        None => {
+            let _ = tray.set_tooltip(Some(TOOLTIP));
+            let _ = tray.set_title(None::<&str>);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+import { invoke, isTauri } from "$lib/tauri";
+
+export interface LiveActivity {
+  active_sessions: number;
+  tokens_per_minute: number;
+  cost_per_hour: number;
+  unpriced_models: string[];
+  tool_calls_per_minute: number;
+  window_minutes: number;
+  tokens_series: number[];
+  cost_series: number[];
+  tool_calls_series: number[];
+}
+
+export async function getLiveActivity(): Promise<LiveActivity> {
+  return invoke<LiveActivity>("get_live_activity");
+}
+
+export async function onActivityUpdate(
+  callback: (activity: LiveActivity) => void,
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<LiveActivity>("activity-updated", (event) => callback(event.payload));
+}
@@ -0,0 +1,13 @@
+import { invoke } from "$lib/tauri";
+import type { UsageRow } from "$lib/commands/usage";
+
+export type HistoryGroupBy = "day" | "model" | "project" | "session";
+
+export async function getUsageHistory(
+  groupBy: HistoryGroupBy,
+  from?: string | null,
+  to?: string | null,
+  project?: string | null,
+): Promise<UsageRow[]> {
+  return invoke<UsageRow[]>("get_usage_history", { groupBy, from, to, project });
+}
@@ -0,0 +1,16 @@
+import { invoke } from "$lib/tauri";
+
+export interface BackupInfo {
+  id: string;
+  file: string;
+  created_at: string;
+  size: number;
+}
+
+export async function listBackups(file?: string | null): Promise<BackupInfo[]> {
+  return invoke<BackupInfo[]>("list_backups", { file });
+}
+
+export async function restoreBackup(file: string, id: string): Promise<void> {
+  return invoke<void>("restore_backup", { file, id });
+}
@@ -0,0 +1,32 @@
+import { invoke } from "$lib/tauri";
+import type { TokenUsage } from "$lib/commands/watcher";
+import type { CostEstimate } from "$lib/commands/pricing";
+
+export interface UsageBlock {
+  start: string;
+  end: string;
+  first_activity: string;
+  last_activity: string;
+  messages: number;
+  tokens: TokenUsage;
+  total_tokens: number;
+  cost: CostEstimate;
+}
+
+export interface BlockStatus {
+  current: UsageBlock | null;
+  resets_in_secs: number | null;
+  tokens_per_minute: number;
+  cost_per_hour: number;
+  projected_tokens: number;
+  projected_cost_usd: number;
+  token_limit: number | null;
+  limit_source: "config" | "historical_max" | null;
+  will_hit_limit: boolean;
+  minutes_until_limit: number | null;
+  recent: UsageBlock[];
+}
+
+export async function getUsageBlocks(): Promise<BlockStatus> {
+  return invoke<BlockStatus>("get_usage_blocks");
+}
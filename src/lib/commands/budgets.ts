@@ -0,0 +1,36 @@
+import { invoke, isTauri } from "$lib/tauri";
+
+export type BudgetPeriod = "daily" | "weekly" | "monthly";
+export type BudgetMetric = "tokens" | "cost";
+
+export interface Budget {
+  project?: string | null;
+  period: BudgetPeriod;
+  metric: BudgetMetric;
+  limit: number;
+}
+
+export interface BudgetStatus {
+  budget: Budget;
+  used: number;
+  fraction: number;
+  period_start: string;
+  resets_on: string;
+  threshold: number | null;
+  unpriced_models: string[];
+}
+
+export interface BudgetAlert {
+  status: BudgetStatus;
+  threshold: number;
+}
+
+export async function getBudgetStatus(): Promise<BudgetStatus[]> {
+  return invoke<BudgetStatus[]>("get_budget_status");
+}
+
+export async function onBudgetAlert(callback: (alert: BudgetAlert) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<BudgetAlert>("budget-alert", (event) => callback(event.payload));
+}
@@ -0,0 +1,32 @@
+import { invoke } from "$lib/tauri";
+
+export interface CacheStats {
+  key: string;
+  messages: number;
+  input_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  hit_ratio: number | null;
+  saved_usd: number;
+  unpriced_models: string[];
+}
+
+export interface SessionCache extends CacheStats {
+  project: string;
+  poor_reuse: boolean;
+}
+
+export interface CacheReport {
+  total: CacheStats;
+  by_project: CacheStats[];
+  by_day: CacheStats[];
+  by_session: SessionCache[];
+}
+
+export async function getCacheReport(
+  project?: string | null,
+  from?: string | null,
+  to?: string | null,
+): Promise<CacheReport> {
+  return invoke<CacheReport>("get_cache_report", { project, from, to });
+}
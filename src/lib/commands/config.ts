@@ -0,0 +1,19 @@
+import { invoke } from "$lib/tauri";
+import type { ModelRates } from "$lib/commands/pricing";
+import type { Budget } from "$lib/commands/budgets";
+
+export interface CockpitConfig {
+  claude_path: string;
+  terminal_command: string[];
+  pricing: Record<string, ModelRates>;
+  budgets: Budget[];
+  block_token_limit: number | null;
+}
+
+export async function readCockpitConfig(): Promise<CockpitConfig> {
+  return invoke<CockpitConfig>("read_cockpit_config");
+}
+
+export async function writeCockpitConfig(config: CockpitConfig): Promise<void> {
+  return invoke<void>("write_cockpit_config", { config });
+}
@@ -0,0 +1,22 @@
+import { invoke } from "$lib/tauri";
+
+export type ExportFormat = "csv" | "jsonl";
+export type ExportGranularity = "message" | "session" | "day" | "project";
+
+export interface ExportOptions {
+  format: ExportFormat;
+  granularity: ExportGranularity;
+  from?: string | null;
+  to?: string | null;
+  project?: string | null;
+  path?: string | null;
+}
+
+export interface ExportResult {
+  path: string;
+  rows: number;
+}
+
+export async function exportUsage(options: ExportOptions): Promise<ExportResult> {
+  return invoke<ExportResult>("export_usage", { options });
+}
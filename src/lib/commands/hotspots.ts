@@ -0,0 +1,27 @@
+import { invoke } from "$lib/tauri";
+
+export interface FileHotspot {
+  path: string;
+  edits: number;
+  lines_added: number;
+  lines_removed: number;
+  lines_changed: number;
+  sessions: number;
+  last_edited: string;
+}
+
+export interface HotspotReport {
+  project: string;
+  edits: number;
+  sessions: number;
+  files: FileHotspot[];
+}
+
+export async function getFileHotspots(
+  project: string,
+  from?: string | null,
+  to?: string | null,
+  limit?: number | null,
+): Promise<HotspotReport> {
+  return invoke<HotspotReport>("get_file_hotspots", { project, from, to, limit });
+}
@@ -0,0 +1,15 @@
+import { invoke } from "$lib/tauri";
+
+export interface LaunchResult {
+  pid: number;
+  cwd: string;
+  command: string[];
+}
+
+export async function resumeSession(sessionPath: string): Promise<LaunchResult> {
+  return invoke<LaunchResult>("resume_session", { sessionPath });
+}
+
+export async function launchSession(projectPath: string): Promise<LaunchResult> {
+  return invoke<LaunchResult>("launch_session", { projectPath });
+}
@@ -0,0 +1,30 @@
+import { invoke } from "$lib/tauri";
+
+export interface ModelRates {
+  input: number;
+  output: number;
+  cache_write_5m: number;
+  cache_write_1h: number;
+  cache_read: number;
+}
+
+export interface PricingEntry {
+  pattern: string;
+  rates: ModelRates;
+  source: "override" | "builtin";
+}
+
+export interface CostEstimate {
+  cost_usd: number;
+  unpriced_models: string[];
+  unpriced_tokens: number;
+}
+
+export async function getPricingTable(): Promise<PricingEntry[]> {
+  return invoke<PricingEntry[]>("get_pricing_table");
+}
+
+export function formatCost(cost: CostEstimate): string {
+  const dollars = `$${cost.cost_usd.toFixed(2)}`;
+  return cost.unpriced_models.length > 0 ? `${dollars}+` : dollars;
+}
@@ -8,6 +8,12 @@ export interface ActiveSession {
   message_count: number;
   last_message_preview: string;
   model: string;
+  pid: number | null;
+  uptime_secs: number | null;
+  cpu_percent: number | null;
+  rss_bytes: number | null;
+  process_alive: boolean;
+  process_match: "open_file" | "heuristic" | null;
 }
 
 export interface TailMessage {
@@ -17,6 +23,8 @@ export interface TailMessage {
   model: string;
   tokens_in: number;
   tokens_out: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
 }
 
 export interface TailResult {
@@ -24,6 +32,15 @@ export interface TailResult {
   total_lines: number;
 }
 
+export type SessionSignal = "interrupt" | "terminate";
+
+export interface SignalResult {
+  pid: number;
+  signal: SessionSignal;
+  state: "exited" | "interrupted" | "running";
+  stopped: boolean;
+}
+
 export async function listActiveSessions(
   thresholdSecs?: number,
 ): Promise<ActiveSession[]> {
@@ -36,3 +53,17 @@ export async function tailSession(
 ): Promise<TailResult> {
   return invoke<TailResult>("tail_session", { filePath, fromLine });
 }
+
+export async function interruptSession(
+  filePath: string,
+  expectedPid: number,
+): Promise<SignalResult> {
+  return invoke<SignalResult>("interrupt_session", { filePath, expectedPid });
+}
+
+export async function terminateSession(
+  filePath: string,
+  expectedPid: number,
+): Promise<SignalResult> {
+  return invoke<SignalResult>("terminate_session", { filePath, expectedPid });
+}
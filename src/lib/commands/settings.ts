@@ -3,6 +3,82 @@ import { invoke } from "$lib/tauri";
 export type FileType = "settings" | "settings_local";
 export type PermissionCategory = "allow" | "deny" | "ask";
 
+export interface SettingsIssue {
+  path: string;
+  severity: "error" | "warning";
+  message: string;
+}
+
+export type SettingsSource = "global_settings" | "global_local" | "project" | "project_local" | "managed";
+
+export interface SourcedValue {
+  source: SettingsSource;
+  path: string;
+  value: unknown;
+}
+
+export interface KeyProvenance {
+  path: string;
+  source: SettingsSource;
+  value: unknown;
+  overridden: SourcedValue[];
+  locked: boolean;
+}
+
+export interface EffectiveSettings {
+  settings: Record<string, unknown>;
+  provenance: KeyProvenance[];
+  managed_file: string | null;
+}
+
+export type Decision = "allow" | "ask" | "deny";
+
+export interface MatchedRule {
+  decision: Decision;
+  rule: string;
+  source: SettingsSource | null;
+  command: string;
+}
+
+export interface Simulation {
+  tool: string;
+  input: string;
+  decision: Decision;
+  matched: MatchedRule[];
+  reason: string;
+}
+
+export type LintKind = "malformed" | "duplicate" | "shadowed" | "contradicted" | "too_broad";
+
+export type SuggestedFix =
+  | { action: "remove"; description: string }
+  | { action: "replace"; description: string; with: string };
+
+export interface LintFinding {
+  kind: LintKind;
+  severity: "error" | "warning";
+  scope: string;
+  file_type: FileType;
+  category: PermissionCategory;
+  rule: string;
+  message: string;
+  fix: SuggestedFix;
+}
+
+export interface RuleSuggestion {
+  rule: string;
+  tool: string;
+  calls: number;
+  sessions: number;
+  examples: string[];
+  last_used: string;
+}
+
+export interface SettingsWriteResult {
+  written: boolean;
+  issues: SettingsIssue[];
+}
+
 export async function readSettings(
   scope: string,
   fileType: FileType,
@@ -17,14 +93,20 @@ export async function writeSettings(
   scope: string,
   fileType: FileType,
   content: Record<string, unknown>,
-): Promise<void> {
-  return invoke<void>("write_settings", { scope, fileType, content });
+): Promise<SettingsWriteResult> {
+  return invoke<SettingsWriteResult>("write_settings", { scope, fileType, content });
+}
+
+export async function validateSettings(
+  content: Record<string, unknown>,
+): Promise<SettingsIssue[]> {
+  return invoke<SettingsIssue[]>("validate_settings", { content });
 }
 
 export async function getEffectiveSettings(
   projectPath: string,
-): Promise<Record<string, unknown>> {
-  return invoke<Record<string, unknown>>("get_effective_settings", {
+): Promise<EffectiveSettings> {
+  return invoke<EffectiveSettings>("get_effective_settings", {
     projectPath,
   });
 }
@@ -56,3 +138,28 @@ export async function removePermission(
     permission,
   });
 }
+
+export async function simulatePermission(
+  projectPath: string,
+  call: string,
+): Promise<Simulation> {
+  return invoke<Simulation>("simulate_permission", { projectPath, call });
+}
+
+export async function lintPermissions(): Promise<LintFinding[]> {
+  return invoke<LintFinding[]>("lint_permissions");
+}
+
+export async function applyLintFix(finding: LintFinding): Promise<void> {
+  await removePermission(finding.scope, finding.file_type, finding.category, finding.rule);
+  if (finding.fix.action === "replace") {
+    await addPermission(finding.scope, finding.file_type, finding.category, finding.fix.with);
+  }
+}
+
+export async function getPermissionSuggestions(
+  project: string,
+  limit?: number,
+): Promise<RuleSuggestion[]> {
+  return invoke<RuleSuggestion[]>("get_permission_suggestions", { project, limit });
+}
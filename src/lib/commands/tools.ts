@@ -0,0 +1,37 @@
+import { invoke } from "$lib/tauri";
+
+export interface ToolStats {
+  name: string;
+  server: string | null;
+  calls: number;
+  sessions: number;
+  completed: number;
+  errors: number;
+  error_rate: number;
+  avg_latency_ms: number | null;
+  p50_latency_ms: number | null;
+  p95_latency_ms: number | null;
+}
+
+export interface ToolBreakdown {
+  key: string;
+  calls: number;
+  errors: number;
+  tools: ToolStats[];
+}
+
+export interface ToolReport {
+  calls: number;
+  errors: number;
+  tools: ToolStats[];
+  by_project: ToolBreakdown[];
+  by_day: ToolBreakdown[];
+}
+
+export async function getToolAnalytics(
+  project?: string | null,
+  from?: string | null,
+  to?: string | null,
+): Promise<ToolReport> {
+  return invoke<ToolReport>("get_tool_analytics", { project, from, to });
+}
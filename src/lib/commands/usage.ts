@@ -1,17 +1,20 @@
 import { invoke } from "$lib/tauri";
+import type { TokenUsage } from "$lib/commands/watcher";
+import type { CostEstimate } from "$lib/commands/pricing";
 
 export interface StatsCache {
-  version: number;
-  lastComputedDate: string;
+  version: number | null;
+  lastComputedDate: string | null;
   dailyActivity: DayActivity[];
-  dailyModelTokens?: Record<string, unknown>;
+  dailyModelTokens: DailyModelTokens[];
   modelUsage: Record<string, ModelUsage>;
-  totalSessions: number;
-  totalMessages: number;
+  totalSessions: number | null;
+  totalMessages: number | null;
   longestSession: LongestSession | null;
-  firstSessionDate: string;
+  firstSessionDate: string | null;
   hourCounts: Record<string, number>;
-  totalSpeculationTimeSavedMs?: number;
+  totalSpeculationTimeSavedMs: number | null;
+  [field: string]: unknown;
 }
 
 export interface DayActivity {
@@ -21,6 +24,11 @@ export interface DayActivity {
   toolCallCount: number;
 }
 
+export interface DailyModelTokens {
+  date: string;
+  tokensByModel: Record<string, number>;
+}
+
 export interface ModelUsage {
   inputTokens: number;
   outputTokens: number;
@@ -39,6 +47,49 @@ export interface LongestSession {
   timestamp: string;
 }
 
-export async function readStatsCache(): Promise<StatsCache | null> {
-  return invoke<StatsCache | null>("read_stats_cache");
+export interface StatsCacheReport {
+  file: string | null;
+  schema_version: number | null;
+  newer_schema: boolean;
+  stats: StatsCache;
+  derived_fields: string[];
+}
+
+export async function readStatsCache(): Promise<StatsCacheReport> {
+  return invoke<StatsCacheReport>("read_stats_cache");
+}
+
+export interface UsageRow {
+  key: string;
+  messages: number;
+  tokens: TokenUsage;
+  total_tokens: number;
+  cost: CostEstimate;
+}
+
+export interface SessionUsage {
+  session_id: string;
+  project: string;
+  file_path: string;
+  first_timestamp: string | null;
+  last_timestamp: string | null;
+  messages: number;
+  tokens: TokenUsage;
+  total_tokens: number;
+  cost: CostEstimate;
+}
+
+export interface UsageReport {
+  messages: number;
+  tokens: TokenUsage;
+  total_tokens: number;
+  cost: CostEstimate;
+  by_day: UsageRow[];
+  by_model: UsageRow[];
+  by_project: UsageRow[];
+  by_session: SessionUsage[];
+}
+
+export async function getUsageReport(): Promise<UsageReport> {
+  return invoke<UsageReport>("get_usage_report");
 }
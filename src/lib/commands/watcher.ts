@@ -10,6 +10,26 @@ export type WatchEvent =
   | "entity-changed"
   | "history-changed";
 
+export interface TokenUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_creation_1h_input_tokens: number;
+  cache_read_input_tokens: number;
+}
+
+export interface SessionCompleted {
+  session_id: string;
+  project: string;
+  title: string;
+  duration_secs: number;
+  tokens: TokenUsage;
+  total_tokens: number;
+  cost_usd: number | null;
+  model: string;
+  final_message_preview: string;
+}
+
 type UnlistenFn = () => void;
 
 export async function onFileChange(